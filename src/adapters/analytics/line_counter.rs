@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use crate::domain::analytics::{class_selected, CountingLine, LineCounts};
+use crate::domain::detection::Detection;
+
+/// Actualizaciones sin ver un track tras las que se olvida su última posición.
+const STALE_UPDATES: u64 = 900;
+
+/// Líneas de conteo de una cámara con sus contadores. Cuenta sobre detecciones con `track_id`:
+/// un cruce es el paso de la posición anterior de un track a la actual de un lado al otro de la línea.
+#[derive(Default)]
+pub struct LineCounter {
+    lines: Vec<CountingLine>,
+    counts: Vec<LineCounts>,
+    /// (línea, track) -> último punto fuera de la línea y actualización en que se vio.
+    last: HashMap<(usize, u64), ([f32; 2], u64)>,
+    updates: u64,
+}
+
+impl LineCounter {
+    /// Sustituye las líneas; las que no cambian conservan sus contadores.
+    pub fn set_lines(&mut self, lines: Vec<CountingLine>) {
+        self.counts = lines
+            .iter()
+            .map(|line| match self.lines.iter().position(|l| l == line) {
+                Some(i) => self.counts[i].clone(),
+                None => LineCounts { name: line.name.clone(), ..Default::default() },
+            })
+            .collect();
+        self.lines = lines;
+        self.last.clear();
+    }
+
+    pub fn lines(&self) -> &[CountingLine] {
+        &self.lines
+    }
+
+    pub fn counts(&self) -> &[LineCounts] {
+        &self.counts
+    }
+
+    pub fn reset(&mut self) {
+        for counts in &mut self.counts {
+            *counts = LineCounts { name: counts.name.clone(), ..Default::default() };
+        }
+    }
+
+    pub fn update(&mut self, detections: &[Detection]) {
+        self.updates += 1;
+        let Self { lines, counts, last, updates } = self;
+
+        for (i, line) in lines.iter().enumerate() {
+            for det in detections.iter().filter(|d| class_selected(&line.classes, d)) {
+                let Some(track_id) = det.track_id else { continue };
+                let point = line.anchor.point(det);
+                // Sobre la línea no se decide nada: se espera a que quede a un lado.
+                if side(line, point) == 0.0 {
+                    continue;
+                }
+                let Some((prev, _)) = last.insert((i, track_id), (point, *updates)) else { continue };
+                match crossing(line, prev, point) {
+                    Some(true) => {
+                        *counts[i].entries.entry(det.label.clone()).or_default() += 1;
+                        counts[i].total_in += 1;
+                    }
+                    Some(false) => {
+                        *counts[i].exits.entry(det.label.clone()).or_default() += 1;
+                        counts[i].total_out += 1;
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        last.retain(|_, (_, seen)| *updates - *seen <= STALE_UPDATES);
+    }
+}
+
+/// Positivo a la derecha de la línea (mirando de `start` a `end`, con el eje Y hacia abajo).
+fn side(line: &CountingLine, p: [f32; 2]) -> f32 {
+    cross(line.start, line.end, p)
+}
+
+fn cross(a: [f32; 2], b: [f32; 2], p: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+/// `Some(true)` si el movimiento `from -> to` cruza la línea hacia su lado derecho (entrada),
+/// `Some(false)` hacia el izquierdo (salida) y `None` si no la cruza.
+fn crossing(line: &CountingLine, from: [f32; 2], to: [f32; 2]) -> Option<bool> {
+    let (d0, d1) = (side(line, from), side(line, to));
+    if d0 * d1 >= 0.0 {
+        return None;
+    }
+    // Tiene que pasar entre los extremos, no por la prolongación de la línea.
+    if cross(from, to, line.start) * cross(from, to, line.end) > 0.0 {
+        return None;
+    }
+    Some(d1 > 0.0)
+}
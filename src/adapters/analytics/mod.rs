@@ -0,0 +1,6 @@
+pub mod tracker;
+pub mod line_counter;
+pub mod zone_monitor;
+pub mod rule_engine;
+pub mod snapshot_store;
+pub mod track_events;
@@ -0,0 +1,118 @@
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::domain::analytics::{class_selected, ZoneStats};
+use crate::domain::detection::Detection;
+use crate::domain::events::{Event, EventKind};
+use crate::domain::rules::{Rule, RuleCondition};
+
+/// Reglas de una cámara con su estado de disparo.
+#[derive(Default)]
+pub struct RuleEngine {
+    rules: Vec<Rule>,
+    states: Vec<RuleState>,
+}
+
+#[derive(Clone, Copy)]
+struct RuleState {
+    /// La condición ha dejado de cumplirse desde el último disparo.
+    armed: bool,
+    last_fired: Option<Instant>,
+}
+
+impl Default for RuleState {
+    fn default() -> Self {
+        Self { armed: true, last_fired: None }
+    }
+}
+
+impl RuleEngine {
+    /// Sustituye las reglas; las que no cambian conservan su estado de disparo.
+    pub fn set_rules(&mut self, rules: Vec<Rule>) {
+        self.states = rules
+            .iter()
+            .map(|rule| self.rules.iter().position(|r| r == rule).map(|i| self.states[i]).unwrap_or_default())
+            .collect();
+        self.rules = rules;
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Evalúa las reglas sobre el resultado del frame capturado en `now` y devuelve los eventos
+    /// disparados.
+    pub fn evaluate(&mut self, camera: &str, detections: &[Detection], zones: &[ZoneStats], now: Instant) -> Vec<Event> {
+        let mut events = Vec::new();
+        for (rule, state) in self.rules.iter().zip(&mut self.states) {
+            let Some(kind) = rule.enabled.then(|| check(rule, detections, zones)).flatten() else {
+                state.armed = true;
+                continue;
+            };
+            // Se compara en segundos `f32`: convertir `cooldown_s` a `Duration` podría entrar en pánico.
+            let cooled = state
+                .last_fired
+                .is_none_or(|t| now.duration_since(t).as_secs_f32() >= rule.cooldown_s);
+            if state.armed && cooled {
+                state.armed = false;
+                state.last_fired = Some(now);
+                events.push(Event { camera: camera.to_string(), timestamp_ms: unix_ms(), kind });
+            }
+        }
+        events
+    }
+}
+
+/// El evento que produciría la regla si su condición se cumple ahora.
+fn check(rule: &Rule, detections: &[Detection], zones: &[ZoneStats]) -> Option<EventKind> {
+    let name = rule.name.clone();
+    match &rule.condition {
+        RuleCondition::Dwell { zone, classes, seconds } => {
+            let stats = zones.iter().find(|z| &z.name == zone)?;
+            let (label, dwell) = stats
+                .dwell
+                .iter()
+                .filter(|(label, _)| label_selected(classes, label))
+                .map(|(label, d)| (label, d.current_max_s))
+                .max_by(|a, b| a.1.total_cmp(&b.1))?;
+            (dwell > *seconds).then(|| EventKind::Dwell {
+                rule: name,
+                zone: zone.clone(),
+                label: label.clone(),
+                seconds: dwell,
+            })
+        }
+        RuleCondition::Count { classes, zone, op, value } => {
+            let count = match zone {
+                Some(zone) => zones
+                    .iter()
+                    .find(|z| &z.name == zone)?
+                    .occupancy
+                    .iter()
+                    .filter(|(label, _)| label_selected(classes, label))
+                    .map(|(_, n)| n)
+                    .sum(),
+                None => detections.iter().filter(|d| class_selected(classes, d)).count() as u32,
+            };
+            op.holds(count, *value).then(|| EventKind::Count { rule: name, zone: zone.clone(), count })
+        }
+        RuleCondition::Detected { classes, min_score } => detections
+            .iter()
+            .filter(|d| d.score >= *min_score && class_selected(classes, d))
+            .max_by(|a, b| a.score.total_cmp(&b.score))
+            .map(|d| EventKind::Detected {
+                rule: name,
+                label: d.label.clone(),
+                score: d.score,
+                track_id: d.track_id,
+            }),
+    }
+}
+
+/// Las estadísticas de zona solo llevan la etiqueta: se compara sin distinguir mayúsculas.
+fn label_selected(classes: &[String], label: &str) -> bool {
+    classes.is_empty() || classes.iter().any(|c| c.trim().to_lowercase() == label.to_lowercase())
+}
+
+fn unix_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+}
@@ -0,0 +1,40 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Recortes JPEG guardados para los eventos. Se sirven en `/api/snapshots/{id}`.
+const CAPACITY: usize = 256;
+
+/// Almacén en memoria de los últimos recortes; al llenarse descarta los más antiguos.
+#[derive(Default)]
+pub struct SnapshotStore {
+    inner: Mutex<Snapshots>,
+}
+
+#[derive(Default)]
+struct Snapshots {
+    next_id: u64,
+    items: VecDeque<(u64, Arc<Vec<u8>>)>,
+}
+
+impl SnapshotStore {
+    /// Guarda un JPEG y devuelve su identificador.
+    pub fn insert(&self, jpeg: Vec<u8>) -> u64 {
+        let mut inner = self.inner.lock().unwrap();
+        inner.next_id += 1;
+        let id = inner.next_id;
+        if inner.items.len() == CAPACITY {
+            inner.items.pop_front();
+        }
+        inner.items.push_back((id, Arc::new(jpeg)));
+        id
+    }
+
+    pub fn get(&self, id: u64) -> Option<Arc<Vec<u8>>> {
+        let inner = self.inner.lock().unwrap();
+        inner.items.iter().find(|(i, _)| *i == id).map(|(_, jpeg)| jpeg.clone())
+    }
+}
+
+pub fn snapshot_path(id: u64) -> String {
+    format!("/api/snapshots/{id}")
+}
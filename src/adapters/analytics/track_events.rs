@@ -0,0 +1,131 @@
+use image::RgbImage;
+use std::collections::HashMap;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::adapters::analytics::snapshot_store::{snapshot_path, SnapshotStore};
+use crate::domain::detection::Detection;
+use crate::domain::events::{Event, EventKind, TrackEvent};
+use crate::domain::geometry::crop_rect;
+use crate::domain::model::TrackEventOptions;
+
+/// Margen alrededor de la caja en los recortes, como fracción de su tamaño.
+const SNAPSHOT_PADDING: f32 = 0.1;
+
+/// Convierte el ciclo de vida de los tracks en eventos de aparición, permanencia y desaparición.
+pub struct TrackEvents {
+    options: TrackEventOptions,
+    tracks: HashMap<u64, TrackLife>,
+}
+
+struct TrackLife {
+    label: String,
+    first_seen: Instant,
+    last_seen: Instant,
+    hits: u32,
+    appeared: bool,
+    stayed: bool,
+    best_score: f32,
+    /// Recorte de la mejor detección, pendiente de codificar.
+    best_crop: Option<RgbImage>,
+    /// Ruta del recorte ya guardado; se invalida al mejorar la puntuación.
+    snapshot: Option<String>,
+}
+
+impl TrackEvents {
+    pub fn new(options: TrackEventOptions) -> Self {
+        Self { options, tracks: HashMap::new() }
+    }
+
+    /// Actualiza los tracks con las detecciones del frame `rgb`, capturado en `now`, y los
+    /// descartados por el tracker (`lost`), y devuelve los eventos producidos.
+    pub fn update(
+        &mut self,
+        camera: &str,
+        detections: &[Detection],
+        lost: &[u64],
+        rgb: &RgbImage,
+        now: Instant,
+        snapshots: &SnapshotStore,
+    ) -> Vec<Event> {
+        let mut kinds = Vec::new();
+
+        for det in detections {
+            let Some(track_id) = det.track_id else { continue };
+            let life = self.tracks.entry(track_id).or_insert_with(|| TrackLife {
+                label: det.label.clone(),
+                first_seen: now,
+                last_seen: now,
+                hits: 0,
+                appeared: false,
+                stayed: false,
+                best_score: 0.0,
+                best_crop: None,
+                snapshot: None,
+            });
+            life.label.clone_from(&det.label);
+            life.last_seen = now;
+            life.hits += 1;
+            if det.score > life.best_score {
+                life.best_score = det.score;
+                if self.options.snapshots {
+                    life.best_crop = crop_rect(det, SNAPSHOT_PADDING, (rgb.width(), rgb.height()))
+                        .map(|(x, y, w, h)| image::imageops::crop_imm(rgb, x, y, w, h).to_image());
+                    life.snapshot = None;
+                }
+            }
+            if !life.appeared && life.hits >= self.options.min_hits {
+                life.appeared = true;
+                kinds.push(EventKind::Appeared(life.event(track_id, snapshots)));
+            }
+        }
+
+        if self.options.stay_s > 0.0 {
+            for (&track_id, life) in &mut self.tracks {
+                if life.appeared && !life.stayed && life.duration_s() >= self.options.stay_s {
+                    life.stayed = true;
+                    kinds.push(EventKind::Stayed(life.event(track_id, snapshots)));
+                }
+            }
+        }
+
+        for track_id in lost {
+            if let Some(mut life) = self.tracks.remove(track_id) {
+                if life.appeared {
+                    kinds.push(EventKind::Disappeared(life.event(*track_id, snapshots)));
+                }
+            }
+        }
+
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64);
+        kinds
+            .into_iter()
+            .map(|kind| Event { camera: camera.to_string(), timestamp_ms, kind })
+            .collect()
+    }
+}
+
+impl TrackLife {
+    fn duration_s(&self) -> f32 {
+        self.last_seen.duration_since(self.first_seen).as_secs_f32()
+    }
+
+    fn event(&mut self, track_id: u64, snapshots: &SnapshotStore) -> TrackEvent {
+        if self.snapshot.is_none() {
+            self.snapshot = self.best_crop.as_ref().and_then(encode_jpeg).map(|jpeg| snapshot_path(snapshots.insert(jpeg)));
+        }
+        TrackEvent {
+            track_id,
+            label: self.label.clone(),
+            duration_s: self.duration_s(),
+            best_score: self.best_score,
+            snapshot: self.snapshot.clone(),
+        }
+    }
+}
+
+fn encode_jpeg(crop: &RgbImage) -> Option<Vec<u8>> {
+    let mut jpeg = Vec::new();
+    let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 85);
+    encoder.encode_image(crop).ok()?;
+    Some(jpeg)
+}
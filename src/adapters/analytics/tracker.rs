@@ -0,0 +1,335 @@
+use crate::domain::geometry::box_iou;
+use crate::domain::detection::Detection;
+use crate::domain::model::TrackerOptions;
+
+/// IoU mínimo de la segunda pasada (detecciones dudosas), el mismo que usa ByteTrack.
+const LOW_MATCH_IOU: f32 = 0.5;
+// Ruido del filtro proporcional a la altura de la caja (valores de ByteTrack/DeepSORT).
+const STD_WEIGHT_POSITION: f32 = 1.0 / 20.0;
+const STD_WEIGHT_VELOCITY: f32 = 1.0 / 160.0;
+
+/// Asigna identificadores estables a las detecciones de inferencias sucesivas.
+pub struct Tracker {
+    options: TrackerOptions,
+    tracks: Vec<Track>,
+    next_id: u64,
+    /// Tracks descartados en la última actualización.
+    lost: Vec<u64>,
+}
+
+struct Track {
+    id: u64,
+    class_id: usize,
+    filter: BoxFilter,
+    age: u32,
+    /// Inferencias seguidas sin detección asociada.
+    missed: u32,
+}
+
+impl Tracker {
+    pub fn new(options: TrackerOptions) -> Self {
+        Self { options, tracks: Vec::new(), next_id: 1, lost: Vec::new() }
+    }
+
+    pub fn options(&self) -> &TrackerOptions {
+        &self.options
+    }
+
+    /// Identificadores de los tracks que la última actualización dio por perdidos.
+    pub fn lost(&self) -> &[u64] {
+        &self.lost
+    }
+
+    /// Asocia las detecciones de una inferencia a los tracks y les pone `track_id` y `track_age`.
+    /// Las que no se asocian ni tienen puntuación para abrir un track quedan sin identificador.
+    pub fn update(&mut self, detections: &mut [Detection]) {
+        for track in &mut self.tracks {
+            track.filter.predict();
+            track.age += 1;
+        }
+        let predicted: Vec<[f32; 4]> = self.tracks.iter().map(|t| t.filter.bbox()).collect();
+        let classes: Vec<usize> = self.tracks.iter().map(|t| t.class_id).collect();
+        let mut assoc = Association {
+            track_used: vec![false; self.tracks.len()],
+            det_track: vec![None; detections.len()],
+        };
+
+        let (high, low): (Vec<usize>, Vec<usize>) =
+            (0..detections.len()).partition(|&d| detections[d].score >= self.options.high_threshold);
+
+        // 1ª pasada: detecciones seguras contra todos los tracks, también los perdidos.
+        let all: Vec<usize> = (0..self.tracks.len()).collect();
+        assoc.greedy(&high, &all, detections, &predicted, &classes, self.options.match_iou);
+        // 2ª pasada: las dudosas solo mantienen tracks que seguían vivos en la inferencia anterior.
+        let active: Vec<usize> = all.into_iter().filter(|&t| self.tracks[t].missed == 0).collect();
+        assoc.greedy(&low, &active, detections, &predicted, &classes, LOW_MATCH_IOU);
+
+        for (det, track) in detections.iter_mut().zip(&assoc.det_track) {
+            if let Some(track) = track.map(|t| &mut self.tracks[t]) {
+                track.filter.update(det_box(det));
+                track.missed = 0;
+                det.track_id = Some(track.id);
+                det.track_age = Some(track.age);
+            }
+        }
+        for (track, used) in self.tracks.iter_mut().zip(&assoc.track_used) {
+            if !used {
+                track.missed += 1;
+            }
+        }
+        let buffer = self.options.track_buffer;
+        self.lost = self.tracks.iter().filter(|t| t.missed > buffer).map(|t| t.id).collect();
+        self.tracks.retain(|t| t.missed <= buffer);
+
+        for &d in &high {
+            let det = &mut detections[d];
+            if assoc.det_track[d].is_some() || det.score < self.options.new_track_threshold {
+                continue;
+            }
+            let id = self.next_id;
+            self.next_id += 1;
+            self.tracks.push(Track {
+                id,
+                class_id: det.class_id,
+                filter: BoxFilter::new(det_box(det)),
+                age: 0,
+                missed: 0,
+            });
+            det.track_id = Some(id);
+            det.track_age = Some(0);
+        }
+    }
+}
+
+struct Association {
+    track_used: Vec<bool>,
+    det_track: Vec<Option<usize>>,
+}
+
+impl Association {
+    /// Empareja por IoU descendente entre la caja predicha y la detección, solo dentro de la misma clase.
+    fn greedy(
+        &mut self,
+        dets: &[usize],
+        tracks: &[usize],
+        detections: &[Detection],
+        predicted: &[[f32; 4]],
+        classes: &[usize],
+        min_iou: f32,
+    ) {
+        let mut pairs = Vec::new();
+        for &d in dets {
+            let bbox = det_box(&detections[d]);
+            for &t in tracks.iter().filter(|&&t| !self.track_used[t] && classes[t] == detections[d].class_id) {
+                let iou = box_iou(predicted[t], bbox);
+                if iou >= min_iou {
+                    pairs.push((iou, d, t));
+                }
+            }
+        }
+        pairs.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+
+        for (_, d, t) in pairs {
+            if self.det_track[d].is_none() && !self.track_used[t] {
+                self.det_track[d] = Some(t);
+                self.track_used[t] = true;
+            }
+        }
+    }
+}
+
+fn det_box(det: &Detection) -> [f32; 4] {
+    [det.x1, det.y1, det.x2, det.y2]
+}
+
+/// Filtro de Kalman de velocidad constante sobre (cx, cy, aspecto, alto), con cada
+/// componente independiente: el de ByteTrack sin covarianzas cruzadas entre ejes.
+struct BoxFilter {
+    axes: [AxisState; 4],
+}
+
+/// Posición, velocidad y su covarianza 2x2 en un eje.
+#[derive(Clone, Copy)]
+struct AxisState {
+    pos: f32,
+    vel: f32,
+    pp: f32,
+    pv: f32,
+    vv: f32,
+}
+
+impl BoxFilter {
+    fn new(bbox: [f32; 4]) -> Self {
+        let m = measure(bbox);
+        let h = m[3].max(1.0);
+        let pos_std = [2.0 * STD_WEIGHT_POSITION * h, 2.0 * STD_WEIGHT_POSITION * h, 1e-2, 2.0 * STD_WEIGHT_POSITION * h];
+        let vel_std = [10.0 * STD_WEIGHT_VELOCITY * h, 10.0 * STD_WEIGHT_VELOCITY * h, 1e-5, 10.0 * STD_WEIGHT_VELOCITY * h];
+        Self {
+            axes: std::array::from_fn(|i| AxisState {
+                pos: m[i],
+                vel: 0.0,
+                pp: pos_std[i].powi(2),
+                pv: 0.0,
+                vv: vel_std[i].powi(2),
+            }),
+        }
+    }
+
+    fn predict(&mut self) {
+        let h = self.axes[3].pos.max(1.0);
+        let q_pos = [STD_WEIGHT_POSITION * h, STD_WEIGHT_POSITION * h, 1e-2, STD_WEIGHT_POSITION * h];
+        let q_vel = [STD_WEIGHT_VELOCITY * h, STD_WEIGHT_VELOCITY * h, 1e-5, STD_WEIGHT_VELOCITY * h];
+        for (i, a) in self.axes.iter_mut().enumerate() {
+            a.pos += a.vel;
+            a.pp += 2.0 * a.pv + a.vv + q_pos[i].powi(2);
+            a.pv += a.vv;
+            a.vv += q_vel[i].powi(2);
+        }
+    }
+
+    fn update(&mut self, bbox: [f32; 4]) {
+        let m = measure(bbox);
+        let h = self.axes[3].pos.max(1.0);
+        let r = [STD_WEIGHT_POSITION * h, STD_WEIGHT_POSITION * h, 1e-1, STD_WEIGHT_POSITION * h];
+        for (i, a) in self.axes.iter_mut().enumerate() {
+            let s = a.pp + r[i].powi(2);
+            let (kp, kv) = (a.pp / s, a.pv / s);
+            let innovation = m[i] - a.pos;
+            a.pos += kp * innovation;
+            a.vel += kv * innovation;
+            a.vv -= kv * a.pv;
+            a.pv *= 1.0 - kp;
+            a.pp *= 1.0 - kp;
+        }
+    }
+
+    fn bbox(&self) -> [f32; 4] {
+        let [cx, cy, aspect, h] = self.axes.map(|a| a.pos);
+        let w = aspect * h;
+        [cx - w / 2.0, cy - h / 2.0, cx + w / 2.0, cy + h / 2.0]
+    }
+}
+
+/// Caja `[x1, y1, x2, y2]` -> `[cx, cy, ancho/alto, alto]`.
+fn measure(bbox: [f32; 4]) -> [f32; 4] {
+    let w = bbox[2] - bbox[0];
+    let h = bbox[3] - bbox[1];
+    [bbox[0] + w / 2.0, bbox[1] + h / 2.0, w / h.max(1e-6), h]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn det(x: f32, y: f32, score: f32, class_id: usize) -> Detection {
+        Detection {
+            x1: x,
+            y1: y,
+            x2: x + 40.0,
+            y2: y + 80.0,
+            score,
+            class_id,
+            label: String::new(),
+            mask: None,
+            keypoints: Vec::new(),
+            obb: None,
+            attributes: Default::default(),
+            track_id: None,
+            track_age: None,
+        }
+    }
+
+    fn tracker() -> Tracker {
+        Tracker::new(TrackerOptions { enabled: true, track_buffer: 5, ..Default::default() })
+    }
+
+    /// Aplica el tracker a una secuencia de frames y devuelve los `track_id` de cada uno.
+    fn replay(tracker: &mut Tracker, frames: Vec<Vec<Detection>>) -> Vec<Vec<Option<u64>>> {
+        frames
+            .into_iter()
+            .map(|mut dets| {
+                tracker.update(&mut dets);
+                dets.iter().map(|d| d.track_id).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_ids_of_objects_moving_in_opposite_directions() {
+        let frames = (0..20)
+            .map(|i| {
+                let i = i as f32;
+                vec![det(10.0 + 12.0 * i, 100.0, 0.9, 0), det(400.0 - 12.0 * i, 110.0, 0.8, 0)]
+            })
+            .collect();
+        let ids = replay(&mut tracker(), frames);
+        assert!(ids.iter().all(|f| f == &vec![Some(1), Some(2)]));
+    }
+
+    #[test]
+    fn reports_track_age() {
+        let mut tracker = tracker();
+        let mut last = Vec::new();
+        for i in 0..4 {
+            last = vec![det(10.0 + 5.0 * i as f32, 10.0, 0.9, 0)];
+            tracker.update(&mut last);
+        }
+        assert_eq!(last[0].track_age, Some(3));
+    }
+
+    #[test]
+    fn recovers_track_after_short_occlusion() {
+        let mut frames: Vec<Vec<Detection>> = (0..5).map(|i| vec![det(10.0 * i as f32, 50.0, 0.9, 0)]).collect();
+        frames.extend((0..3).map(|_| Vec::new()));
+        // Reaparece donde lo lleva la velocidad estimada.
+        frames.push(vec![det(80.0, 50.0, 0.9, 0)]);
+        let ids = replay(&mut tracker(), frames);
+        assert_eq!(ids.last().unwrap(), &vec![Some(1)]);
+    }
+
+    #[test]
+    fn opens_new_track_after_buffer_expires() {
+        let mut frames = vec![vec![det(10.0, 10.0, 0.9, 0)]];
+        frames.extend((0..7).map(|_| Vec::new()));
+        frames.push(vec![det(10.0, 10.0, 0.9, 0)]);
+        let ids = replay(&mut tracker(), frames);
+        assert_eq!(ids.last().unwrap(), &vec![Some(2)]);
+    }
+
+    #[test]
+    fn reports_lost_tracks_once() {
+        let mut tracker = tracker();
+        tracker.update(&mut vec![det(10.0, 10.0, 0.9, 0)]);
+        let lost: Vec<Vec<u64>> = (0..8)
+            .map(|_| {
+                tracker.update(&mut Vec::new());
+                tracker.lost().to_vec()
+            })
+            .collect();
+        assert_eq!(lost.iter().filter(|l| !l.is_empty()).collect::<Vec<_>>(), vec![&vec![1]]);
+        assert_eq!(lost[5], vec![1]);
+    }
+
+    #[test]
+    fn low_score_detection_keeps_active_track() {
+        let frames = vec![
+            vec![det(10.0, 10.0, 0.9, 0)],
+            vec![det(12.0, 10.0, 0.3, 0)],
+            vec![det(14.0, 10.0, 0.9, 0)],
+        ];
+        let ids = replay(&mut tracker(), frames);
+        assert_eq!(ids, vec![vec![Some(1)], vec![Some(1)], vec![Some(1)]]);
+    }
+
+    #[test]
+    fn low_score_detection_does_not_open_track() {
+        let ids = replay(&mut tracker(), vec![vec![det(10.0, 10.0, 0.3, 0)], vec![det(10.0, 10.0, 0.55, 0)]]);
+        assert_eq!(ids, vec![vec![None], vec![None]]);
+    }
+
+    #[test]
+    fn does_not_match_across_classes() {
+        let ids = replay(&mut tracker(), vec![vec![det(10.0, 10.0, 0.9, 0)], vec![det(10.0, 10.0, 0.9, 1)]]);
+        assert_eq!(ids, vec![vec![Some(1)], vec![Some(2)]]);
+    }
+}
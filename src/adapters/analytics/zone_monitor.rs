@@ -0,0 +1,176 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use crate::domain::analytics::{class_selected, Containment, DwellStats, Zone, ZoneStats};
+use crate::domain::detection::Detection;
+use crate::domain::geometry::{clip_polygon, polygon_area};
+
+/// Tiempo fuera de la zona (o sin ver el track) tras el que se da una visita por terminada.
+/// Absorbe parpadeos del detector y salidas momentáneas por el borde.
+const EXIT_GRACE: Duration = Duration::from_secs(1);
+
+/// Zonas de una cámara con su ocupación y permanencia. La ocupación cuenta todas las
+/// detecciones dentro; la permanencia solo las que tienen `track_id`.
+#[derive(Default)]
+pub struct ZoneMonitor {
+    zones: Vec<Zone>,
+    states: Vec<ZoneState>,
+    stats: Vec<ZoneStats>,
+}
+
+#[derive(Default)]
+struct ZoneState {
+    /// Tracks dentro de la zona.
+    present: HashMap<u64, Presence>,
+    /// Visitas terminadas por etiqueta.
+    visits: BTreeMap<String, VisitTotals>,
+}
+
+struct Presence {
+    label: String,
+    entered: Instant,
+    last_inside: Instant,
+}
+
+#[derive(Default)]
+struct VisitTotals {
+    count: u64,
+    total_s: f32,
+    max_s: f32,
+}
+
+impl ZoneMonitor {
+    /// Sustituye las zonas; las que no cambian conservan su estado.
+    pub fn set_zones(&mut self, zones: Vec<Zone>) {
+        let mut old: Vec<Option<(ZoneState, ZoneStats)>> = std::mem::take(&mut self.states)
+            .into_iter()
+            .zip(std::mem::take(&mut self.stats))
+            .map(Some)
+            .collect();
+        (self.states, self.stats) = zones
+            .iter()
+            .map(|zone| {
+                self.zones
+                    .iter()
+                    .position(|z| z == zone)
+                    .and_then(|i| old[i].take())
+                    .unwrap_or_else(|| (ZoneState::default(), ZoneStats { name: zone.name.clone(), ..Default::default() }))
+            })
+            .unzip();
+        self.zones = zones;
+    }
+
+    pub fn zones(&self) -> &[Zone] {
+        &self.zones
+    }
+
+    pub fn stats(&self) -> &[ZoneStats] {
+        &self.stats
+    }
+
+    /// Olvida las visitas terminadas; los objetos que siguen dentro conservan su entrada.
+    pub fn reset(&mut self) {
+        for (state, stats) in self.states.iter_mut().zip(&mut self.stats) {
+            state.visits.clear();
+            for dwell in stats.dwell.values_mut() {
+                dwell.visits = 0;
+                dwell.visit_avg_s = 0.0;
+                dwell.visit_max_s = 0.0;
+            }
+        }
+    }
+
+    /// Actualiza las zonas con las detecciones del frame capturado en `now`.
+    pub fn update(&mut self, detections: &[Detection], now: Instant) {
+        for ((zone, state), stats) in self.zones.iter().zip(&mut self.states).zip(&mut self.stats) {
+            let mut occupancy: BTreeMap<String, u32> = BTreeMap::new();
+            for det in detections.iter().filter(|d| class_selected(&zone.classes, d) && contains(zone, d)) {
+                *occupancy.entry(det.label.clone()).or_default() += 1;
+                if let Some(track_id) = det.track_id {
+                    let presence = state.present.entry(track_id).or_insert_with(|| Presence {
+                        label: det.label.clone(),
+                        entered: now,
+                        last_inside: now,
+                    });
+                    presence.label.clone_from(&det.label);
+                    presence.last_inside = now;
+                }
+            }
+
+            let ZoneState { present, visits } = state;
+            present.retain(|_, p| {
+                if now.duration_since(p.last_inside) <= EXIT_GRACE {
+                    return true;
+                }
+                let dwell = p.last_inside.duration_since(p.entered).as_secs_f32();
+                let totals = visits.entry(p.label.clone()).or_default();
+                totals.count += 1;
+                totals.total_s += dwell;
+                totals.max_s = totals.max_s.max(dwell);
+                false
+            });
+
+            let mut dwell: BTreeMap<String, DwellStats> = visits
+                .iter()
+                .map(|(label, t)| {
+                    let stats = DwellStats {
+                        visits: t.count,
+                        visit_avg_s: t.total_s / t.count as f32,
+                        visit_max_s: t.max_s,
+                        ..Default::default()
+                    };
+                    (label.clone(), stats)
+                })
+                .collect();
+            let mut current: BTreeMap<&str, (u32, f32)> = BTreeMap::new();
+            for p in present.values() {
+                let entry = dwell.entry(p.label.clone()).or_default();
+                let seconds = now.duration_since(p.entered).as_secs_f32();
+                entry.current_max_s = entry.current_max_s.max(seconds);
+                let (n, sum) = current.entry(&p.label).or_default();
+                *n += 1;
+                *sum += seconds;
+            }
+            for (label, (n, sum)) in current {
+                if let Some(entry) = dwell.get_mut(label) {
+                    entry.current_avg_s = sum / n as f32;
+                }
+            }
+
+            stats.total = occupancy.values().sum();
+            stats.occupancy = occupancy;
+            stats.dwell = dwell;
+        }
+    }
+}
+
+/// Aplica el criterio de pertenencia de la zona a la caja de la detección.
+fn contains(zone: &Zone, det: &Detection) -> bool {
+    let cx = (det.x1 + det.x2) / 2.0;
+    match zone.containment {
+        Containment::Center => point_in_polygon(&zone.polygon, [cx, (det.y1 + det.y2) / 2.0]),
+        Containment::Bottom => point_in_polygon(&zone.polygon, [cx, det.y2]),
+        Containment::Overlap => {
+            let area = (det.x2 - det.x1) * (det.y2 - det.y1);
+            if area <= 0.0 {
+                return false;
+            }
+            // El polígono de la zona puede ser cóncavo; la caja es el recorte convexo.
+            let rect = [[det.x1, det.y1], [det.x2, det.y1], [det.x2, det.y2], [det.x1, det.y2]];
+            polygon_area(&clip_polygon(&zone.polygon, &rect)) / area >= zone.min_overlap
+        }
+    }
+}
+
+/// Regla par-impar: cuenta los cruces de un rayo horizontal hacia la derecha.
+fn point_in_polygon(polygon: &[[f32; 2]], p: [f32; 2]) -> bool {
+    let mut inside = false;
+    let n = polygon.len();
+    for i in 0..n {
+        let (a, b) = (polygon[i], polygon[(i + n - 1) % n]);
+        if (a[1] > p[1]) != (b[1] > p[1]) && p[0] < a[0] + (p[1] - a[1]) * (b[0] - a[0]) / (b[1] - a[1]) {
+            inside = !inside;
+        }
+    }
+    inside
+}
@@ -4,7 +4,7 @@ pub mod ws;
 
 use axum::{routing::{get, post}, Router};
 use crate::adapters::http::state::HttpState;
-use crate::adapters::http::ws::ws_handler;
+use crate::adapters::http::ws::{events_ws_handler, ws_handler};
 
 pub fn router(state: HttpState) -> Router {
     Router::new()
@@ -15,6 +15,18 @@ pub fn router(state: HttpState) -> Router {
         .route("/api/cameras/:index/controls", get(routes::list_controls_by_index))
         .route("/api/cameras/:index/controls", post(routes::set_controls_by_index))
         .route("/api/files", get(routes::list_files)) // Nueva ruta
+        .route("/api/models/inspect", get(routes::inspect_model))
+        .route("/api/cameras/:index/lines", get(routes::list_lines_by_index))
+        .route("/api/cameras/:index/lines", post(routes::set_lines_by_index))
+        .route("/api/cameras/:index/counts", get(routes::line_counts_by_index))
+        .route("/api/cameras/:index/counts/reset", post(routes::reset_counts_by_index))
+        .route("/api/cameras/:index/zones", get(routes::list_zones_by_index))
+        .route("/api/cameras/:index/zones", post(routes::set_zones_by_index))
+        .route("/api/cameras/:index/zones/stats", get(routes::zone_stats_by_index))
+        .route("/api/cameras/:index/rules", get(routes::list_rules_by_index))
+        .route("/api/cameras/:index/rules", post(routes::set_rules_by_index))
+        .route("/api/snapshots/:id", get(routes::get_snapshot))
         .route("/ws/stream", get(ws_handler))
+        .route("/ws/events", get(events_ws_handler))
         .with_state(state)
 }
\ No newline at end of file
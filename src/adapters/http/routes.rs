@@ -1,17 +1,33 @@
-use axum::{extract::Query, extract::Path, extract::State, http::StatusCode, response::IntoResponse, Json};
-use serde::Deserialize;
+use axum::{extract::Query, extract::Path, extract::State, http::header, http::StatusCode, response::IntoResponse, Json};
+use serde::{de::DeserializeOwned, Deserialize};
 use serde_json::json;
 use std::fs;
 use std::path::{Path as FsPath, PathBuf};
 
 use crate::adapters::http::state::HttpState;
 use crate::application::dto::ConfigurePipelineRequest;
+use crate::domain::analytics::{CountingLine, Zone};
+use crate::domain::camera::{CameraId, CameraMode, FrameSize};
+use crate::domain::errors::DomainError;
+use crate::domain::rules::Rule;
+use crate::domain::model::{default_session_cache, InferenceConfig, ModelId, ModelTask, RuntimeOptions, SecondaryModel, YoloParams};
 
 #[derive(Deserialize)]
 pub struct FileQuery {
     path: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct FormatQuery {
+    format: Option<String>,
+}
+
+/// Documento de reglas, en JSON o YAML.
+#[derive(Deserialize, serde::Serialize)]
+struct RulesDocument {
+    rules: Vec<Rule>,
+}
+
 fn get_video_path(idx: u32) -> String {
     format!("/dev/video{idx}")
 }
@@ -85,19 +101,114 @@ pub async fn list_files(Query(query): Query<FileQuery>) -> impl IntoResponse {
     .into_response()
 }
 
-pub async fn get_config() -> impl IntoResponse {
-    Json(json!({
-        "camera_index": 0,
-        "fourcc": "MJPG",
-        "width": 640,
-        "height": 480,
-        "fps": 30,
-        "model_path": "models/yolo11n.onnx",
-        "imgsz": 640,
-        "conf_thres": 0.25,
-        "iou_thres": 0.45,
-        "max_det": 100
-    }))
+/// Inspecciona un `.onnx` bajo MODEL_ROOT: entradas, salidas, opset, metadatos y hash.
+pub async fn inspect_model(State(st): State<HttpState>, Query(query): Query<FileQuery>) -> impl IntoResponse {
+    let path = match safe_join(&model_root(), &query.path.unwrap_or_default()) {
+        Ok(p) => p,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response(),
+    };
+
+    let model = ModelId {
+        name: "yolo".into(),
+        onnx_path: path.to_string_lossy().to_string(),
+        labels: Vec::new(),
+    };
+    match st.pipeline.inspect_model(&model).await {
+        Ok(info) => Json(info).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn get_config(State(st): State<HttpState>) -> impl IntoResponse {
+    let (cam, mode, infer) = match st.pipeline.current_config().await {
+        Ok(Some(current)) => current,
+        _ => default_config(),
+    };
+    let mut config = config_to_json(&cam, &mode, &infer);
+    let status = st.pipeline.engine_status().await.ok().flatten();
+    config["active_provider"] = json!(status.as_ref().map(|s| s.provider));
+    config["active_preprocess"] = json!(status.as_ref().map(|s| &s.preprocess));
+    config["fixed_input"] = json!(status.as_ref().and_then(|s| s.fixed_input));
+    config["active_model"] = json!(status.map(|s| s.model_path));
+    let load = st.pipeline.model_load_state().await.unwrap_or_default();
+    config["model_loading"] = json!(load.loading);
+    config["model_error"] = json!(load.last_error);
+    Json(config)
+}
+
+/// Configuración sugerida al dashboard mientras no se haya aplicado ninguna.
+fn default_config() -> (CameraId, CameraMode, InferenceConfig) {
+    (
+        CameraId { path: get_video_path(0) },
+        CameraMode {
+            format: "MJPG".into(),
+            size: FrameSize { width: 640, height: 480 },
+            fps: 30,
+        },
+        InferenceConfig {
+            model: ModelId {
+                name: "yolo".into(),
+                onnx_path: "models/yolo11n.onnx".into(),
+                labels: Vec::new(),
+            },
+            params: YoloParams::default(),
+            task: ModelTask::Auto,
+            runtime: RuntimeOptions::default(),
+            preprocess: None,
+            secondary: Vec::new(),
+            session_cache: default_session_cache(),
+            schedule: Default::default(),
+            tracker: Default::default(),
+        },
+    )
+}
+
+fn config_to_json(cam: &CameraId, mode: &CameraMode, infer: &InferenceConfig) -> serde_json::Value {
+    json!({
+        "camera_index": camera_index(&cam.path),
+        "fourcc": mode.format,
+        "width": mode.size.width,
+        "height": mode.size.height,
+        "fps": mode.fps,
+        "model_path": infer.model.onnx_path,
+        "imgsz": infer.params.input_width.max(infer.params.input_height),
+        "input_width": infer.params.input_width,
+        "input_height": infer.params.input_height,
+        "conf_thres": infer.params.conf_threshold,
+        "iou_thres": infer.params.iou_threshold,
+        "max_det": infer.params.max_detections,
+        "agnostic_nms": infer.params.agnostic_nms,
+        "resize_mode": infer.params.resize_mode,
+        "output_format": infer.params.output_format,
+        "task": infer.task,
+        "top_k": infer.params.top_k,
+        "class_thresholds": infer.params.class_thresholds,
+        "include_classes": infer.params.include_classes,
+        "exclude_classes": infer.params.exclude_classes,
+        "runtime": infer.runtime,
+        "preprocess": infer.preprocess,
+        "secondary": infer.secondary,
+        "session_cache": infer.session_cache,
+        "schedule": infer.schedule,
+        "tracker": infer.tracker,
+        "class_map": infer.params.class_map,
+        "tiling": infer.params.tiling,
+        // Etiquetas tal y como se publican (con la tabla de clases aplicada) y las originales del modelo.
+        "labels": infer.params.class_map.resolve(&infer.model.labels).0,
+        "model_labels": infer.model.labels
+    })
+}
+
+fn camera_index(path: &str) -> u32 {
+    path.chars()
+        .filter(|ch| ch.is_ascii_digit())
+        .collect::<String>()
+        .parse::<u32>()
+        .unwrap_or(0)
 }
 
 pub async fn list_cameras(State(st): State<HttpState>) -> impl IntoResponse {
@@ -105,17 +216,7 @@ pub async fn list_cameras(State(st): State<HttpState>) -> impl IntoResponse {
         Ok(cameras) => {
             let res: Vec<_> = cameras
                 .into_iter()
-                .map(|c| {
-                    let idx = c
-                        .id
-                        .path
-                        .chars()
-                        .filter(|ch| ch.is_ascii_digit())
-                        .collect::<String>()
-                        .parse::<u32>()
-                        .unwrap_or(0);
-                    json!({ "index": idx, "card": c.card, "path": c.id.path })
-                })
+                .map(|c| json!({ "index": camera_index(&c.id.path), "card": c.card, "path": c.id.path }))
                 .collect();
             Json(res).into_response()
         }
@@ -178,19 +279,180 @@ pub async fn set_controls_by_index(
     Json(json!({ "ok": true })).into_response()
 }
 
+pub async fn list_lines_by_index(State(st): State<HttpState>, Path(idx): Path<u32>) -> impl IntoResponse {
+    let cam = CameraId { path: get_video_path(idx) };
+    match st.analytics.lines(cam).await {
+        Ok(lines) => Json(lines).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+/// Sustituye las líneas de conteo de la cámara: `{ "lines": [...] }`.
+pub async fn set_lines_by_index(
+    State(st): State<HttpState>,
+    Path(idx): Path<u32>,
+    Json(req): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    let cam = CameraId { path: get_video_path(idx) };
+    let lines: Vec<CountingLine> = match serde_json::from_value(req["lines"].clone()) {
+        Ok(lines) => lines,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(json!({ "error": format!("Líneas inválidas: {}", e) })))
+                .into_response()
+        }
+    };
+    match st.analytics.set_lines(cam, lines).await {
+        Ok(_) => Json(json!({ "ok": true })).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+pub async fn line_counts_by_index(State(st): State<HttpState>, Path(idx): Path<u32>) -> impl IntoResponse {
+    let cam = CameraId { path: get_video_path(idx) };
+    match st.analytics.line_counts(cam).await {
+        Ok(counts) => Json(counts).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+pub async fn reset_counts_by_index(State(st): State<HttpState>, Path(idx): Path<u32>) -> impl IntoResponse {
+    let cam = CameraId { path: get_video_path(idx) };
+    match st.analytics.reset_counts(cam).await {
+        Ok(_) => Json(json!({ "ok": true })).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+pub async fn list_zones_by_index(State(st): State<HttpState>, Path(idx): Path<u32>) -> impl IntoResponse {
+    let cam = CameraId { path: get_video_path(idx) };
+    match st.analytics.zones(cam).await {
+        Ok(zones) => Json(zones).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+/// Sustituye las zonas de la cámara: `{ "zones": [...] }`.
+pub async fn set_zones_by_index(
+    State(st): State<HttpState>,
+    Path(idx): Path<u32>,
+    Json(req): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    let cam = CameraId { path: get_video_path(idx) };
+    let zones: Vec<Zone> = match serde_json::from_value(req["zones"].clone()) {
+        Ok(zones) => zones,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(json!({ "error": format!("Zonas inválidas: {}", e) })))
+                .into_response()
+        }
+    };
+    match st.analytics.set_zones(cam, zones).await {
+        Ok(_) => Json(json!({ "ok": true })).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+pub async fn zone_stats_by_index(State(st): State<HttpState>, Path(idx): Path<u32>) -> impl IntoResponse {
+    let cam = CameraId { path: get_video_path(idx) };
+    match st.analytics.zone_stats(cam).await {
+        Ok(stats) => Json(stats).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+/// Reglas de la cámara en JSON, o en YAML con `?format=yaml`.
+pub async fn list_rules_by_index(
+    State(st): State<HttpState>,
+    Path(idx): Path<u32>,
+    Query(query): Query<FormatQuery>,
+) -> impl IntoResponse {
+    let cam = CameraId { path: get_video_path(idx) };
+    let rules = match st.analytics.rules(cam).await {
+        Ok(rules) => rules,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))).into_response(),
+    };
+    if query.format.as_deref() == Some("yaml") {
+        let yaml = serde_yaml::to_string(&RulesDocument { rules }).unwrap_or_default();
+        return ([(header::CONTENT_TYPE, "application/yaml")], yaml).into_response();
+    }
+    Json(RulesDocument { rules }).into_response()
+}
+
+/// Sustituye las reglas de la cámara: `{ "rules": [...] }` en JSON o el mismo documento en YAML.
+/// Se guardan en `RULES_PATH` y se recuperan al arrancar.
+pub async fn set_rules_by_index(State(st): State<HttpState>, Path(idx): Path<u32>, body: String) -> impl IntoResponse {
+    let cam = CameraId { path: get_video_path(idx) };
+    // Un documento JSON también es YAML válido: basta con un parser.
+    let doc: RulesDocument = match serde_yaml::from_str(&body) {
+        Ok(doc) => doc,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(json!({ "error": format!("Reglas inválidas: {}", e) })))
+                .into_response()
+        }
+    };
+    match st.analytics.set_rules(cam, doc.rules).await {
+        Ok(_) => Json(json!({ "ok": true })).into_response(),
+        // No se pudieron guardar en disco: no es culpa de la petición.
+        Err(e @ DomainError::OperationFailed(_)) => {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))).into_response()
+        }
+        Err(e) => (StatusCode::BAD_REQUEST, Json(json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+/// Recorte JPEG enlazado desde un evento de track.
+pub async fn get_snapshot(State(st): State<HttpState>, Path(id): Path<u64>) -> impl IntoResponse {
+    match st.analytics.snapshot(id).await {
+        Ok(Some(jpeg)) => ([(header::CONTENT_TYPE, "image/jpeg")], jpeg).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(json!({ "error": "Recorte no disponible" }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+fn resolve_model_path(raw: &str) -> String {
+    if FsPath::new(raw).is_absolute() {
+        raw.to_string()
+    } else {
+        model_root().join(raw).to_string_lossy().to_string()
+    }
+}
+
 pub async fn apply_config(State(st): State<HttpState>, Json(req): Json<serde_json::Value>) -> impl IntoResponse {
+    let request = match config_request(&req) {
+        Ok(request) => request,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(json!({ "error": format!("Configuración inválida: {}", e) })))
+                .into_response()
+        }
+    };
+    let (cam, mode, infer) = request.into();
+
+    match st.pipeline.configure(cam, mode, infer).await {
+        Ok(_) => Json(json!({ "ok": true })).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Lee la petición de `/api/config`. Los campos simples ausentes toman su valor por defecto; los
+/// objetos anidados mal formados se rechazan en lugar de sustituirse en silencio por los defectos.
+fn config_request(req: &serde_json::Value) -> Result<ConfigurePipelineRequest, String> {
     let idx = req["camera_index"].as_u64().unwrap_or(0) as u32;
 
     // Si el frontend usa /api/files ahora se devuelven rutas relativas a MODEL_ROOT.
     // Para compatibilidad: si el path es relativo lo resolvemos contra MODEL_ROOT.
-    let model_path_raw = req["model_path"].as_str().unwrap_or("").to_string();
-    let model_path = if FsPath::new(&model_path_raw).is_absolute() {
-        model_path_raw
-    } else {
-        model_root().join(&model_path_raw).to_string_lossy().to_string()
-    };
+    let model_path = resolve_model_path(req["model_path"].as_str().unwrap_or(""));
+    let mut secondary: Vec<SecondaryModel> = nested(req, "secondary")?;
+    for model in &mut secondary {
+        model.onnx_path = resolve_model_path(&model.onnx_path);
+    }
 
-    let (cam, mode, infer) = ConfigurePipelineRequest {
+    // `imgsz` da una entrada cuadrada; `input_width`/`input_height` la hacen rectangular.
+    let imgsz = req["imgsz"].as_u64().unwrap_or(640);
+
+    Ok(ConfigurePipelineRequest {
         camera_path: get_video_path(idx),
         fourcc: req["fourcc"].as_str().unwrap_or("MJPG").to_string(),
         width: req["width"].as_u64().unwrap_or(640) as u32,
@@ -199,20 +461,35 @@ pub async fn apply_config(State(st): State<HttpState>, Json(req): Json<serde_jso
         model_name: "yolo".to_string(),
         onnx_path: model_path,
         yolo: crate::domain::model::YoloParams {
-            input_size: req["imgsz"].as_u64().unwrap_or(640) as u32,
+            input_width: req["input_width"].as_u64().unwrap_or(imgsz) as u32,
+            input_height: req["input_height"].as_u64().unwrap_or(imgsz) as u32,
             conf_threshold: req["conf_thres"].as_f64().unwrap_or(0.25) as f32,
             iou_threshold: req["iou_thres"].as_f64().unwrap_or(0.45) as f32,
             max_detections: req["max_det"].as_u64().unwrap_or(100) as usize,
+            agnostic_nms: req["agnostic_nms"].as_bool().unwrap_or(false),
+            resize_mode: nested(req, "resize_mode")?,
+            output_format: nested(req, "output_format")?,
+            top_k: req["top_k"].as_u64().unwrap_or(5) as usize,
+            class_thresholds: nested(req, "class_thresholds")?,
+            include_classes: nested(req, "include_classes")?,
+            exclude_classes: nested(req, "exclude_classes")?,
+            class_map: nested(req, "class_map")?,
+            tiling: nested(req, "tiling")?,
         },
-    }
-    .into();
+        task: nested(req, "task")?,
+        runtime: nested(req, "runtime")?,
+        preprocess: nested(req, "preprocess")?,
+        secondary,
+        session_cache: req["session_cache"].as_u64().map_or_else(default_session_cache, |n| n as usize),
+        schedule: nested(req, "schedule")?,
+        tracker: nested(req, "tracker")?,
+    })
+}
 
-    match st.pipeline.configure(cam, mode, infer).await {
-        Ok(_) => Json(json!({ "ok": true })).into_response(),
-        Err(e) => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+/// Campo anidado de la petición: ausente o `null` da el valor por defecto; mal formado, el error de serde.
+fn nested<T: DeserializeOwned + Default>(req: &serde_json::Value, key: &str) -> Result<T, String> {
+    match req.get(key) {
+        None | Some(serde_json::Value::Null) => Ok(T::default()),
+        Some(value) => serde_json::from_value(value.clone()).map_err(|e| format!("{key}: {e}")),
     }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use crate::application::services::{CameraService, PipelineService};
+use crate::application::services::{AnalyticsService, CameraService, PipelineService};
 
 /// Estado compartido para los manejadores HTTP de Axum.
 /// Siguiendo la Arquitectura Hexagonal, el estado contiene los servicios (Casos de Uso).
@@ -9,4 +9,6 @@ pub struct HttpState {
     pub camera: Arc<CameraService>,
     /// Servicio para orquestar el flujo de captura e inferencia.
     pub pipeline: Arc<PipelineService>,
+    /// Servicio de analítica (líneas de conteo).
+    pub analytics: Arc<AnalyticsService>,
 }
@@ -1,7 +1,9 @@
 use axum::extract::ws::{WebSocketUpgrade, WebSocket, Message};
-use axum::extract::State;
+use axum::extract::{Query, State};
+use serde::Deserialize;
 use crate::adapters::http::state::HttpState;
 use crate::domain::stream::WsFrameMetaMessage;
+use tokio::sync::broadcast::error::RecvError;
 
 pub async fn ws_handler(ws: WebSocketUpgrade, State(st): State<HttpState>) -> impl axum::response::IntoResponse {
     ws.on_upgrade(move |socket| handle_socket(socket, st))
@@ -21,3 +23,42 @@ async fn handle_socket(mut socket: WebSocket, st: HttpState) {
     }
 }
 
+/// Filtro de `/ws/events`: `?types=appeared,disappeared`; sin él llegan todos los eventos.
+#[derive(Deserialize)]
+pub struct EventsQuery {
+    types: Option<String>,
+}
+
+pub async fn events_ws_handler(
+    ws: WebSocketUpgrade,
+    State(st): State<HttpState>,
+    Query(query): Query<EventsQuery>,
+) -> impl axum::response::IntoResponse {
+    let types: Vec<String> = query
+        .types
+        .map(|t| t.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+    ws.on_upgrade(move |socket| handle_events_socket(socket, st, types))
+}
+
+/// Solo eventos, un mensaje JSON por evento y sin frames JPEG.
+async fn handle_events_socket(mut socket: WebSocket, st: HttpState, types: Vec<String>) {
+    let mut rx = match st.analytics.subscribe_events().await {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            // Un cliente lento pierde eventos pero sigue conectado.
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+        let json = serde_json::to_value(&event).unwrap_or_default();
+        if !types.is_empty() && !types.iter().any(|t| json["type"] == t.as_str()) {
+            continue;
+        }
+        if socket.send(Message::Text(json.to_string())).await.is_err() { break; }
+    }
+}
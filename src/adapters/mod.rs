@@ -2,3 +2,5 @@
 pub mod v4l2;     // Corregido: l en lugar de 1 y punto y coma
 pub mod onnx;     // Añadido
 pub mod http;     // Añadido
+pub mod analytics;
+pub mod storage;
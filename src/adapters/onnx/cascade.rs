@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use image::RgbImage;
+use tracing::warn;
+
+use crate::adapters::onnx::yolo_engine::OnnxYoloEngine;
+use crate::domain::analytics::class_selected;
+use crate::domain::detection::Detection;
+use crate::domain::geometry::crop_rect;
+use crate::domain::model::{InferenceConfig, ModelTask, ResizeMode, RuntimeOptions, SecondaryModel, YoloParams};
+
+/// Modelo secundario cargado, listo para clasificar recortes.
+pub struct SecondaryStage {
+    spec: SecondaryModel,
+    engine: OnnxYoloEngine,
+    params: YoloParams,
+}
+
+impl SecondaryStage {
+    pub fn load(spec: &SecondaryModel, runtime: &RuntimeOptions) -> Result<Self> {
+        let engine = OnnxYoloEngine::load(&InferenceConfig {
+            model: spec.model_id(),
+            params: YoloParams::default(),
+            task: ModelTask::Classify,
+            runtime: stage_runtime(spec, runtime),
+            preprocess: spec.preprocess.clone(),
+            secondary: Vec::new(),
+            session_cache: 0,
+            schedule: Default::default(),
+            tracker: Default::default(),
+        })
+        .with_context(|| format!("modelo secundario {}", spec.name))?;
+
+        // Los clasificadores de Ultralytics estiran el recorte a la entrada, sin letterbox.
+        let params = YoloParams {
+            input_width: spec.input_size,
+            input_height: spec.input_size,
+            resize_mode: ResizeMode::Stretch,
+            top_k: spec.top_k,
+            ..Default::default()
+        };
+
+        Ok(Self { spec: spec.clone(), engine, params })
+    }
+
+    pub fn warmup(&mut self) -> Result<()> {
+        self.engine.warmup(&self.params)
+    }
+
+    /// Clasifica el recorte de cada detección seleccionada y guarda el resultado en sus atributos.
+    /// Un recorte que falla se queda sin atributo; la detección se conserva.
+    pub fn apply(&mut self, rgb: &RgbImage, detections: &mut [Detection]) {
+        let mut failed = 0;
+        let mut first_error = None;
+        for det in detections.iter_mut().filter(|d| class_selected(&self.spec.classes, d)) {
+            let Some((x, y, w, h)) = crop_rect(det, self.spec.padding, (rgb.width(), rgb.height())) else {
+                continue;
+            };
+            let crop = image::imageops::crop_imm(rgb, x, y, w, h).to_image();
+            let mut results = match self.engine.infer(&crop, &self.params) {
+                Ok(output) => output.classifications,
+                Err(e) => {
+                    failed += 1;
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            };
+            results.retain(|c| c.score >= self.spec.min_score);
+            if !results.is_empty() {
+                det.attributes.insert(self.spec.name.clone(), results);
+            }
+        }
+        if let Some(e) = first_error {
+            warn!("Modelo secundario {}: fallaron {} recortes: {:#}", self.spec.name, failed, e);
+        }
+    }
+}
+
+/// Las sesiones se crean seguidas y ORT pone en el nombre del profiling una marca de tiempo
+/// con resolución de segundos: cada etapa añade su nombre al prefijo para no pisar al primario.
+fn stage_runtime(spec: &SecondaryModel, runtime: &RuntimeOptions) -> RuntimeOptions {
+    let name: String = spec
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    RuntimeOptions {
+        profiling_path: runtime.profiling_path.as_ref().map(|path| format!("{path}_{name}")),
+        ..runtime.clone()
+    }
+}
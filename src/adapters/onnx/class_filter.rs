@@ -0,0 +1,58 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::detection::Detection;
+use crate::domain::model::YoloParams;
+
+/// Umbrales y listas de clases de `YoloParams` resueltos a `class_id` con las etiquetas del modelo.
+/// Las clases se pueden indicar por nombre (sin distinguir mayúsculas) o por id;
+/// los nombres que el modelo no conoce se ignoran.
+pub struct ClassRules {
+    default_threshold: f32,
+    thresholds: HashMap<usize, f32>,
+    include: Option<HashSet<usize>>,
+    exclude: HashSet<usize>,
+}
+
+impl ClassRules {
+    pub fn new(params: &YoloParams, labels: &[String]) -> Self {
+        let resolve_all = |keys: &[String]| keys.iter().filter_map(|k| resolve_class(k, labels)).collect::<HashSet<_>>();
+
+        Self {
+            default_threshold: params.conf_threshold,
+            thresholds: params
+                .class_thresholds
+                .iter()
+                .filter_map(|(k, &t)| resolve_class(k, labels).map(|id| (id, t)))
+                .collect(),
+            include: (!params.include_classes.is_empty()).then(|| resolve_all(&params.include_classes)),
+            exclude: resolve_all(&params.exclude_classes),
+        }
+    }
+
+    /// Umbral mínimo que deben aplicar los decodificadores para no perder candidatos
+    /// de clases con un umbral propio más bajo que el general.
+    pub fn min_threshold(&self) -> f32 {
+        self.thresholds.values().copied().fold(self.default_threshold, f32::min)
+    }
+
+    /// La clase pasa las listas de inclusión y exclusión.
+    pub fn allows(&self, class_id: usize) -> bool {
+        !self.exclude.contains(&class_id) && self.include.as_ref().is_none_or(|ids| ids.contains(&class_id))
+    }
+
+    /// La detección pasa las listas y supera el umbral de su clase.
+    pub fn accepts(&self, det: &Detection) -> bool {
+        let threshold = self.thresholds.get(&det.class_id).copied().unwrap_or(self.default_threshold);
+        det.score > threshold && self.allows(det.class_id)
+    }
+}
+
+fn resolve_class(key: &str, labels: &[String]) -> Option<usize> {
+    let key = key.trim();
+    key.parse()
+        .ok()
+        .or_else(|| {
+            let key = key.to_lowercase();
+            labels.iter().position(|l| l.to_lowercase() == key)
+        })
+}
@@ -0,0 +1,61 @@
+use std::collections::HashSet;
+
+use crate::adapters::onnx::decoder::Candidate;
+use crate::domain::detection::Classification;
+use crate::domain::model::ClassMapping;
+
+/// `ClassMapping` resuelto contra las etiquetas del modelo.
+pub struct ClassRemap {
+    labels: Vec<String>,
+    targets: Vec<Option<usize>>,
+}
+
+impl ClassRemap {
+    /// `None` si la tabla no cambia nada y se puede saltar el paso.
+    pub fn new(mapping: &ClassMapping, model_labels: &[String]) -> Option<Self> {
+        if mapping.is_identity() {
+            return None;
+        }
+        let (labels, targets) = mapping.resolve(model_labels);
+        Some(Self { labels, targets })
+    }
+
+    /// `class_id` publicado; los ids fuera de las etiquetas del modelo se dejan igual.
+    fn target(&self, class_id: usize) -> Option<usize> {
+        self.targets.get(class_id).copied().unwrap_or(Some(class_id))
+    }
+
+    fn label(&self, class_id: usize, fallback: &str) -> String {
+        self.labels.get(class_id).map_or(fallback, String::as_str).to_string()
+    }
+
+    /// Reescribe `class_id` y `label` y descarta las clases sin destino.
+    pub fn apply(&self, candidates: Vec<Candidate>) -> Vec<Candidate> {
+        candidates
+            .into_iter()
+            .filter_map(|mut c| {
+                let class_id = self.target(c.detection.class_id)?;
+                c.detection.label = self.label(class_id, &c.detection.label);
+                c.detection.class_id = class_id;
+                Some(c)
+            })
+            .collect()
+    }
+
+    /// Igual que `apply` para un ranking de clasificación ordenado por puntuación:
+    /// de las clases fusionadas se queda la primera (la de mayor puntuación).
+    pub fn apply_ranking(&self, ranking: Vec<Classification>, k: usize) -> Vec<Classification> {
+        let mut seen = HashSet::new();
+        ranking
+            .into_iter()
+            .filter_map(|mut c| {
+                let class_id = self.target(c.class_id)?;
+                c.label = self.label(class_id, &c.label);
+                c.class_id = class_id;
+                Some(c)
+            })
+            .filter(|c| seen.insert(c.class_id))
+            .take(k)
+            .collect()
+    }
+}
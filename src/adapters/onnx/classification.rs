@@ -0,0 +1,33 @@
+use crate::adapters::onnx::labels::FALLBACK_LABEL;
+use crate::domain::detection::Classification;
+
+/// Devuelve las `k` clases con mayor probabilidad entre las que admite `allow`.
+/// Si la salida no parece ya una distribución (exportaciones sin softmax), se normaliza.
+pub fn top_k(scores: &[f32], k: usize, labels: &[String], allow: impl Fn(usize) -> bool) -> Vec<Classification> {
+    let probs = if is_distribution(scores) { scores.to_vec() } else { softmax(scores) };
+
+    let mut ranked: Vec<(usize, f32)> = probs.into_iter().enumerate().collect();
+    ranked.sort_unstable_by(|a, b| b.1.total_cmp(&a.1));
+
+    ranked
+        .into_iter()
+        .filter(|&(class_id, _)| allow(class_id))
+        .take(k)
+        .map(|(class_id, score)| Classification {
+            class_id,
+            label: labels.get(class_id).map_or(FALLBACK_LABEL, String::as_str).to_string(),
+            score,
+        })
+        .collect()
+}
+
+fn is_distribution(scores: &[f32]) -> bool {
+    scores.iter().all(|s| (0.0..=1.0).contains(s)) && (scores.iter().sum::<f32>() - 1.0).abs() < 1e-3
+}
+
+fn softmax(scores: &[f32]) -> Vec<f32> {
+    let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = scores.iter().map(|s| (s - max).exp()).collect();
+    let sum: f32 = exps.iter().sum();
+    exps.into_iter().map(|e| e / sum).collect()
+}
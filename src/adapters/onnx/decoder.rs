@@ -0,0 +1,312 @@
+use ndarray::{s, ArrayView1, ArrayView2};
+use std::collections::BTreeMap;
+
+use crate::adapters::onnx::class_map::ClassRemap;
+use crate::adapters::onnx::labels::FALLBACK_LABEL;
+use crate::adapters::onnx::nms::non_max_suppression;
+use crate::adapters::onnx::preprocess::FrameTransform;
+use crate::domain::detection::Detection;
+use crate::domain::model::{OutputFormat, YoloParams};
+
+/// Parámetros comunes a todos los decodificadores.
+pub struct DecodeContext<'a> {
+    pub conf_threshold: f32,
+    pub transform: &'a FrameTransform,
+    pub labels: &'a [String],
+    /// Valores por candidato que siguen a los scores de clase
+    /// (p.ej. 32 coeficientes de máscara en modelos de segmentación).
+    pub num_extra: usize,
+}
+
+/// Candidato decodificado: la detección y sus valores extra, que se conservan
+/// para el post-procesado específico de la tarea tras el NMS.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub detection: Detection,
+    /// Caja `[x1, y1, x2, y2]` en el espacio de entrada del modelo.
+    pub input_box: [f32; 4],
+    pub extra: Vec<f32>,
+}
+
+impl AsRef<Detection> for Candidate {
+    fn as_ref(&self) -> &Detection {
+        &self.detection
+    }
+}
+
+/// Convierte la salida cruda del modelo (sin eje de batch) en candidatos
+/// sobre el frame original.
+pub trait OutputDecoder: Send + Sync {
+    fn decode(&self, output: ArrayView2<f32>, ctx: &DecodeContext) -> Vec<Candidate>;
+
+    /// Los modelos end-to-end ya aplican NMS dentro del grafo.
+    fn needs_nms(&self) -> bool {
+        true
+    }
+}
+
+/// YOLOv8/11: `[4+C+E, N]` con (cx, cy, w, h), un score por clase y E valores extra.
+pub struct YoloV8Decoder;
+
+/// YOLOv5/v7: `[N, 5+C+E]` con (cx, cy, w, h, objectness), un score por clase y E valores extra.
+pub struct YoloV5Decoder;
+
+/// YOLOv10 y exportaciones end-to-end: `[N, 6+E]` con (x1, y1, x2, y2, score, clase).
+pub struct EndToEndDecoder;
+
+/// Filas máximas para suponer una salida end-to-end sin la marca `end2end`: estas exportaciones
+/// emiten unas 300, mientras que un YOLOv5/v7 de una clase (`[N, 6]`) tiene miles de anclas.
+const END_TO_END_MAX_ROWS: usize = 1000;
+
+static YOLO_V8: YoloV8Decoder = YoloV8Decoder;
+static YOLO_V5: YoloV5Decoder = YoloV5Decoder;
+static END_TO_END: EndToEndDecoder = EndToEndDecoder;
+
+/// Elige el decodificador. Con `OutputFormat::Auto` se deduce de la forma de la salida
+/// (`dims` sin batch) y de la marca `end2end` de los metadatos.
+pub fn select_decoder(
+    format: OutputFormat,
+    dims: &[usize],
+    num_extra: usize,
+    end2end_hint: bool,
+) -> &'static dyn OutputDecoder {
+    match format {
+        OutputFormat::YoloV8 => &YOLO_V8,
+        OutputFormat::YoloV5 => &YOLO_V5,
+        OutputFormat::EndToEnd => &END_TO_END,
+        OutputFormat::Auto => {
+            let (rows, cols) = (dims[0], dims[1]);
+            if end2end_hint || (cols == 6 + num_extra && rows > cols && rows <= END_TO_END_MAX_ROWS) {
+                &END_TO_END
+            } else if rows < cols {
+                &YOLO_V8
+            } else {
+                &YOLO_V5
+            }
+        }
+    }
+}
+
+impl OutputDecoder for YoloV8Decoder {
+    fn decode(&self, view: ArrayView2<f32>, ctx: &DecodeContext) -> Vec<Candidate> {
+        let class_end = view.shape()[0].saturating_sub(ctx.num_extra).max(4);
+        let mut candidates = Vec::new();
+
+        for column in view.columns() {
+            let Some((class_id, score)) = best_class(column.slice(s![4..class_end])) else {
+                continue;
+            };
+
+            if score > ctx.conf_threshold {
+                let (cx, cy, w, h) = (column[0], column[1], column[2], column[3]);
+                candidates.push(to_candidate(
+                    [cx - w / 2.0, cy - h / 2.0, cx + w / 2.0, cy + h / 2.0],
+                    score,
+                    class_id,
+                    column.slice(s![class_end..]),
+                    ctx,
+                ));
+            }
+        }
+
+        candidates
+    }
+}
+
+impl OutputDecoder for YoloV5Decoder {
+    fn decode(&self, view: ArrayView2<f32>, ctx: &DecodeContext) -> Vec<Candidate> {
+        let class_end = view.shape()[1].saturating_sub(ctx.num_extra).max(5);
+        let mut candidates = Vec::new();
+
+        for row in view.rows() {
+            let objectness = row[4];
+            if objectness <= ctx.conf_threshold {
+                continue;
+            }
+            let Some((class_id, class_score)) = best_class(row.slice(s![5..class_end])) else {
+                continue;
+            };
+
+            let score = objectness * class_score;
+            if score > ctx.conf_threshold {
+                let (cx, cy, w, h) = (row[0], row[1], row[2], row[3]);
+                candidates.push(to_candidate(
+                    [cx - w / 2.0, cy - h / 2.0, cx + w / 2.0, cy + h / 2.0],
+                    score,
+                    class_id,
+                    row.slice(s![class_end..]),
+                    ctx,
+                ));
+            }
+        }
+
+        candidates
+    }
+}
+
+impl OutputDecoder for EndToEndDecoder {
+    fn decode(&self, view: ArrayView2<f32>, ctx: &DecodeContext) -> Vec<Candidate> {
+        view.rows()
+            .into_iter()
+            .filter(|row| row[4] > ctx.conf_threshold)
+            .map(|row| to_candidate([row[0], row[1], row[2], row[3]], row[4], row[5] as usize, row.slice(s![6..]), ctx))
+            .collect()
+    }
+
+    fn needs_nms(&self) -> bool {
+        false
+    }
+}
+
+/// NMS (si el decodificador lo necesita), reasignación de clases y recorte a `max_detections`,
+/// en ese orden: el límite se aplica sobre las detecciones que sobreviven al NMS.
+pub fn finalize_candidates(
+    mut candidates: Vec<Candidate>,
+    decoder: &dyn OutputDecoder,
+    params: &YoloParams,
+    remap: Option<&ClassRemap>,
+) -> Vec<Candidate> {
+    if decoder.needs_nms() {
+        candidates = non_max_suppression(candidates, params.iou_threshold, params.agnostic_nms);
+    } else {
+        candidates.sort_unstable_by(|a, b| b.detection.score.total_cmp(&a.detection.score));
+    }
+    if let Some(remap) = remap {
+        candidates = remap.apply(candidates);
+        if params.class_map.rerun_nms {
+            candidates = non_max_suppression(candidates, params.iou_threshold, params.agnostic_nms);
+        }
+    }
+    candidates.truncate(params.max_detections);
+    candidates
+}
+
+fn best_class(scores: ArrayView1<f32>) -> Option<(usize, f32)> {
+    scores.iter().copied().enumerate().max_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+/// Construye el candidato proyectando la caja `[x1, y1, x2, y2]` del espacio
+/// de entrada del modelo al frame original.
+fn to_candidate(
+    xyxy: [f32; 4],
+    score: f32,
+    class_id: usize,
+    extra: ArrayView1<f32>,
+    ctx: &DecodeContext,
+) -> Candidate {
+    let (x1, y1) = ctx.transform.project_to_frame(xyxy[0], xyxy[1]);
+    let (x2, y2) = ctx.transform.project_to_frame(xyxy[2], xyxy[3]);
+
+    Candidate {
+        detection: Detection {
+            x1,
+            y1,
+            x2,
+            y2,
+            score,
+            class_id,
+            label: ctx.labels.get(class_id).map_or(FALLBACK_LABEL, String::as_str).to_string(),
+            mask: None,
+            keypoints: Vec::new(),
+            obb: None,
+            attributes: BTreeMap::new(),
+            track_id: None,
+            track_age: None,
+        },
+        input_box: xyxy,
+        extra: extra.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::onnx::preprocess::resize_to_input;
+    use crate::domain::model::ResizeMode;
+    use ndarray::Array2;
+
+    /// Transformación identidad: frame del mismo tamaño que la entrada.
+    fn identity() -> FrameTransform {
+        resize_to_input(&image::RgbImage::new(64, 64), (64, 64), ResizeMode::Stretch, &Default::default()).1
+    }
+
+    /// Salida sintética `[4+C, N]` de YOLOv8 a partir de (cx, cy, w, h, scores por clase),
+    /// rellena con columnas vacías hasta 16 para que la forma se reconozca como YOLOv8.
+    fn yolo_v8_output(boxes: &[([f32; 4], [f32; 2])]) -> Array2<f32> {
+        let mut out = Array2::zeros((6, boxes.len().max(16)));
+        for (i, (xywh, scores)) in boxes.iter().enumerate() {
+            for (r, v) in xywh.iter().chain(scores).enumerate() {
+                out[[r, i]] = *v;
+            }
+        }
+        out
+    }
+
+    fn decode(output: &Array2<f32>) -> Vec<Candidate> {
+        let labels = vec!["a".to_string(), "b".to_string()];
+        let transform = identity();
+        let ctx = DecodeContext { conf_threshold: 0.25, transform: &transform, labels: &labels, num_extra: 0 };
+        select_decoder(OutputFormat::Auto, output.shape(), 0, false).decode(output.view(), &ctx)
+    }
+
+    fn params(agnostic_nms: bool, max_detections: usize) -> YoloParams {
+        YoloParams { iou_threshold: 0.45, agnostic_nms, max_detections, ..Default::default() }
+    }
+
+    #[test]
+    fn decodes_yolo_v8_columns() {
+        let output = yolo_v8_output(&[([20.0, 20.0, 10.0, 10.0], [0.1, 0.9]), ([5.0, 5.0, 2.0, 2.0], [0.2, 0.1])]);
+        let candidates = decode(&output);
+        assert_eq!(candidates.len(), 1);
+        let det = &candidates[0].detection;
+        assert_eq!((det.class_id, det.label.as_str(), det.score), (1, "b", 0.9));
+        assert_eq!([det.x1, det.y1, det.x2, det.y2], [15.0, 15.0, 25.0, 25.0]);
+    }
+
+    #[test]
+    fn suppresses_same_class_overlaps() {
+        let output = yolo_v8_output(&[([20.0, 20.0, 10.0, 10.0], [0.9, 0.0]), ([21.0, 20.0, 10.0, 10.0], [0.8, 0.0])]);
+        let kept = finalize_candidates(decode(&output), &YOLO_V8, &params(false, 100), None);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].detection.score, 0.9);
+    }
+
+    #[test]
+    fn class_aware_keeps_other_classes_and_agnostic_does_not() {
+        let output = yolo_v8_output(&[([20.0, 20.0, 10.0, 10.0], [0.9, 0.0]), ([21.0, 20.0, 10.0, 10.0], [0.0, 0.8])]);
+        assert_eq!(finalize_candidates(decode(&output), &YOLO_V8, &params(false, 100), None).len(), 2);
+        assert_eq!(finalize_candidates(decode(&output), &YOLO_V8, &params(true, 100), None).len(), 1);
+    }
+
+    #[test]
+    fn single_class_yolo_v5_is_not_taken_for_end_to_end() {
+        assert!(select_decoder(OutputFormat::Auto, &[25200, 6], 0, false).needs_nms());
+        assert!(!select_decoder(OutputFormat::Auto, &[300, 6], 0, false).needs_nms());
+        assert!(!select_decoder(OutputFormat::Auto, &[25200, 6], 0, true).needs_nms());
+
+        // Una fila de YOLOv5 de una clase: (cx, cy, w, h, objectness, score).
+        let mut output = Array2::zeros((2000, 6));
+        output.row_mut(0).assign(&ndarray::arr1(&[20.0, 20.0, 10.0, 10.0, 0.9, 0.8]));
+        let candidates = decode(&output);
+        assert_eq!(candidates.len(), 1);
+        let det = &candidates[0].detection;
+        assert_eq!(det.class_id, 0);
+        assert!((det.score - 0.72).abs() < 1e-6);
+        assert_eq!([det.x1, det.y1, det.x2, det.y2], [15.0, 15.0, 25.0, 25.0]);
+    }
+
+    #[test]
+    fn nms_runs_before_max_detections() {
+        // Tres cajas casi iguales con más puntuación que una cuarta separada: con el límite
+        // aplicado antes del NMS la separada se perdería.
+        let output = yolo_v8_output(&[
+            ([20.0, 20.0, 10.0, 10.0], [0.95, 0.0]),
+            ([20.5, 20.0, 10.0, 10.0], [0.9, 0.0]),
+            ([21.0, 20.0, 10.0, 10.0], [0.85, 0.0]),
+            ([50.0, 50.0, 10.0, 10.0], [0.5, 0.0]),
+        ]);
+        let kept = finalize_candidates(decode(&output), &YOLO_V8, &params(false, 2), None);
+        let scores: Vec<f32> = kept.iter().map(|c| c.detection.score).collect();
+        assert_eq!(scores, vec![0.95, 0.5]);
+    }
+}
@@ -0,0 +1,154 @@
+use anyhow::Result;
+use ort::session::Session;
+use ort::value::Outlet;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::adapters::onnx::pose::parse_ints;
+use crate::adapters::onnx::task::resolve_task;
+use crate::domain::model::{ModelInfo, ModelTask, TensorInfo};
+
+/// Carga el modelo y describe sus entradas, salidas, versiones, metadatos y hash.
+pub fn inspect_model(onnx_path: &str) -> Result<ModelInfo> {
+    describe_model(onnx_path, true)
+}
+
+/// Como [`inspect_model`] pero sin calcular el SHA-256 (queda vacío): basta para
+/// validar el modelo y leer sus etiquetas antes de aplicar una configuración.
+pub fn probe_model(onnx_path: &str) -> Result<ModelInfo> {
+    describe_model(onnx_path, false)
+}
+
+fn describe_model(onnx_path: &str, with_hash: bool) -> Result<ModelInfo> {
+    let model_bytes = fs::read(onnx_path)?;
+    let session = Session::builder()?.commit_from_memory(&model_bytes)?;
+
+    let model_metadata = session.metadata()?;
+    let producer = model_metadata.producer();
+    let mut metadata = BTreeMap::new();
+    for key in model_metadata.custom_keys()? {
+        if let Some(value) = model_metadata.custom(&key) {
+            metadata.insert(key, value);
+        }
+    }
+
+    let inputs: Vec<TensorInfo> = session.inputs().iter().map(tensor_info).collect();
+    let outputs: Vec<TensorInfo> = session.outputs().iter().map(tensor_info).collect();
+    let output_ranks: Vec<usize> = outputs.iter().map(|o| o.shape.len()).collect();
+    let task = resolve_task(ModelTask::Auto, metadata.get("task").map(String::as_str), &output_ranks);
+
+    // `imgsz` de Ultralytics puede ser `[640, 640]` o un único entero; si falta, usamos la entrada NCHW.
+    let imgsz = match metadata.get("imgsz").map(|v| parse_ints(v)).as_deref() {
+        Some([h, w]) => Some([*h as u32, *w as u32]),
+        Some([s]) => Some([*s as u32, *s as u32]),
+        _ => match inputs.first().map(|i| i.shape.as_slice()) {
+            Some([_, _, h, w]) if *h > 0 && *w > 0 => Some([*h as u32, *w as u32]),
+            _ => None,
+        },
+    };
+    let stride = metadata.get("stride").and_then(|v| parse_ints(v).first().map(|&s| s as u32));
+
+    let (ir_version, opset) = read_versions(&model_bytes);
+
+    Ok(ModelInfo {
+        path: onnx_path.to_string(),
+        sha256: if with_hash { format!("{:x}", Sha256::digest(&model_bytes)) } else { String::new() },
+        ir_version,
+        opset,
+        producer,
+        inputs,
+        outputs,
+        metadata,
+        task,
+        imgsz,
+        stride,
+    })
+}
+
+fn tensor_info(outlet: &Outlet) -> TensorInfo {
+    let dtype = outlet.dtype();
+    TensorInfo {
+        name: outlet.name().to_string(),
+        dtype: dtype.tensor_type().map_or_else(|| dtype.to_string(), |t| t.to_string()),
+        shape: dtype.tensor_shape().map(|s| s.to_vec()).unwrap_or_default(),
+    }
+}
+
+/// ONNX Runtime no expone `ir_version` ni el opset, así que los leemos recorriendo
+/// los campos de primer nivel del `ModelProto` (1 = ir_version, 8 = opset_import).
+fn read_versions(bytes: &[u8]) -> (Option<i64>, Option<i64>) {
+    let mut ir_version = None;
+    let mut opset = None;
+
+    let mut pos = 0;
+    while let Some((field, value)) = next_field(bytes, &mut pos) {
+        match (field, value) {
+            (1, Field::Varint(v)) => ir_version = Some(v as i64),
+            (8, Field::Bytes(entry)) => {
+                // OperatorSetIdProto: 1 = domain, 2 = version. El dominio estándar es "" o "ai.onnx".
+                let (mut domain, mut version): (&[u8], Option<i64>) = (&[], None);
+                let mut p = 0;
+                while let Some((f, v)) = next_field(entry, &mut p) {
+                    match (f, v) {
+                        (1, Field::Bytes(d)) => domain = d,
+                        (2, Field::Varint(v)) => version = Some(v as i64),
+                        _ => {}
+                    }
+                }
+                if domain.is_empty() || domain == b"ai.onnx" {
+                    opset = version;
+                }
+            }
+            _ => {}
+        }
+    }
+    (ir_version, opset)
+}
+
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Lee el siguiente campo protobuf (número de campo y valor); `None` al final o si está corrupto.
+fn next_field<'a>(bytes: &'a [u8], pos: &mut usize) -> Option<(u64, Field<'a>)> {
+    if *pos >= bytes.len() {
+        return None;
+    }
+    let key = read_varint(bytes, pos)?;
+    let value = match key & 7 {
+        0 => Field::Varint(read_varint(bytes, pos)?),
+        1 => {
+            *pos += 8;
+            Field::Fixed
+        }
+        2 => {
+            let len = read_varint(bytes, pos)? as usize;
+            let end = pos.checked_add(len)?;
+            let data = bytes.get(*pos..end)?;
+            *pos = end;
+            Field::Bytes(data)
+        }
+        5 => {
+            *pos += 4;
+            Field::Fixed
+        }
+        _ => return None,
+    };
+    Some((key >> 3, value))
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
@@ -0,0 +1,140 @@
+use ort::session::Session;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Lista de clases COCO incorporada, usada cuando el modelo no declara las suyas.
+pub const COCO_LABELS: [&str; 80] = [
+    "persona", "bicicleta", "coche", "motocicleta", "avión", "autobús", "tren", "camión", "barco",
+    "semáforo", "hidrante", "señal de stop", "parquímetro", "banco", "pájaro", "gato", "perro",
+    "caballo", "oveja", "vaca", "elefante", "oso", "cebra", "jirafa", "mochila", "paraguas",
+    "bolso", "corbata", "maleta", "frisbee", "esquís", "snowboard", "pelota", "cometa",
+    "bate de béisbol", "guante de béisbol", "monopatín", "tabla de surf", "raqueta de tenis",
+    "botella", "copa de vino", "taza", "tenedor", "cuchillo", "cuchara", "tazón", "plátano",
+    "manzana", "sándwich", "naranja", "brócoli", "zanahoria", "perrito caliente", "pizza",
+    "donut", "pastel", "silla", "sofá", "planta", "cama", "mesa", "inodoro", "televisor",
+    "portátil", "ratón", "mando", "teclado", "móvil", "microondas", "horno", "tostadora",
+    "fregadero", "nevera", "libro", "reloj", "jarrón", "tijeras", "peluche", "secador", "cepillo",
+];
+
+/// Etiqueta usada cuando un `class_id` queda fuera de la lista.
+pub const FALLBACK_LABEL: &str = "objeto";
+
+/// Resuelve las etiquetas del modelo por orden de preferencia:
+/// 1. entrada `names` de los metadatos ONNX (exportaciones Ultralytics),
+/// 2. fichero `<modelo>.labels.txt` / `<modelo>.labels.yaml` junto al `.onnx`,
+/// 3. lista COCO incorporada.
+pub fn resolve_labels(onnx_path: &str, session: &Session) -> Vec<String> {
+    let names = session.metadata().ok().and_then(|m| m.custom("names"));
+    labels_from_names(onnx_path, names.as_deref())
+}
+
+/// Igual que [`resolve_labels`], pero a partir del valor `names` ya leído
+/// (p.ej. de una inspección previa), sin abrir otra sesión.
+pub fn labels_from_names(onnx_path: &str, names: Option<&str>) -> Vec<String> {
+    if let Some(labels) = names.and_then(parse_names_metadata) {
+        return labels;
+    }
+
+    if let Some(labels) = read_sidecar_labels(Path::new(onnx_path)) {
+        return labels;
+    }
+
+    COCO_LABELS.iter().map(|s| s.to_string()).collect()
+}
+
+/// Interpreta el diccionario Python que Ultralytics guarda en `names`,
+/// p.ej. `{0: 'person', 1: "people's"}`.
+fn parse_names_metadata(raw: &str) -> Option<Vec<String>> {
+    let body = raw.trim().strip_prefix('{')?.strip_suffix('}')?;
+    let mut entries = Vec::new();
+    let mut rest = body;
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        if rest.is_empty() {
+            break;
+        }
+
+        let colon = rest.find(':')?;
+        let id: usize = rest[..colon].trim().trim_matches(|c| c == '\'' || c == '"').parse().ok()?;
+        rest = rest[colon + 1..].trim_start();
+
+        let quote = rest.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+        let mut name = String::new();
+        let mut end = None;
+        let mut chars = rest.char_indices().skip(1);
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    if let Some((_, escaped)) = chars.next() {
+                        name.push(escaped);
+                    }
+                }
+                c if c == quote => {
+                    end = Some(i + c.len_utf8());
+                    break;
+                }
+                c => name.push(c),
+            }
+        }
+        rest = &rest[end?..];
+        entries.push((id, name));
+    }
+
+    indexed_to_vec(entries)
+}
+
+fn read_sidecar_labels(onnx_path: &Path) -> Option<Vec<String>> {
+    let txt = sidecar_path(onnx_path, "labels.txt");
+    if let Ok(content) = fs::read_to_string(&txt) {
+        let labels: Vec<String> = content
+            .lines()
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .collect();
+        if !labels.is_empty() {
+            return Some(labels);
+        }
+    }
+
+    let yaml = sidecar_path(onnx_path, "labels.yaml");
+    let content = fs::read_to_string(&yaml).ok()?;
+    let value: serde_yaml::Value = serde_yaml::from_str(&content).ok()?;
+    labels_from_yaml(&value)
+}
+
+fn sidecar_path(onnx_path: &Path, suffix: &str) -> PathBuf {
+    let stem = onnx_path.file_stem().unwrap_or_default().to_string_lossy();
+    onnx_path.with_file_name(format!("{stem}.{suffix}"))
+}
+
+/// Acepta una lista, un mapa `id: nombre` o un documento con clave `names`
+/// (formato de los `data.yaml` de Ultralytics).
+fn labels_from_yaml(value: &serde_yaml::Value) -> Option<Vec<String>> {
+    match value {
+        serde_yaml::Value::Sequence(items) => {
+            let labels: Vec<String> = items.iter().filter_map(|v| v.as_str().map(String::from)).collect();
+            (!labels.is_empty()).then_some(labels)
+        }
+        serde_yaml::Value::Mapping(map) => {
+            if let Some(names) = map.get("names") {
+                return labels_from_yaml(names);
+            }
+            let entries = map
+                .iter()
+                .map(|(k, v)| Some((k.as_u64()? as usize, v.as_str()?.to_string())))
+                .collect::<Option<Vec<_>>>()?;
+            indexed_to_vec(entries)
+        }
+        _ => None,
+    }
+}
+
+fn indexed_to_vec(entries: Vec<(usize, String)>) -> Option<Vec<String>> {
+    let len = entries.iter().map(|(id, _)| id + 1).max()?;
+    let mut labels = vec![FALLBACK_LABEL.to_string(); len];
+    for (id, name) in entries {
+        labels[id] = name;
+    }
+    Some(labels)
+}
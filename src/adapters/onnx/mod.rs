@@ -1,4 +1,21 @@
 pub mod yolo_engine;
 pub mod model_catalog;
 pub mod pipeline;
-
+pub mod nms;
+pub mod preprocess;
+pub mod labels;
+pub mod decoder;
+pub mod segmentation;
+pub mod pose;
+pub mod obb;
+pub mod task;
+pub mod classification;
+pub mod runtime;
+pub mod inspect;
+pub mod tensor_io;
+pub mod class_filter;
+pub mod class_map;
+pub mod tiling;
+pub mod cascade;
+pub mod session_cache;
+pub mod scheduler;
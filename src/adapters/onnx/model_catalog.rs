@@ -1,9 +1,12 @@
 use async_trait::async_trait;
 use std::path::Path;
 
+use crate::adapters::onnx::labels::labels_from_names;
+use crate::adapters::onnx::inspect::{inspect_model, probe_model};
+use crate::adapters::onnx::task::{resolve_task, validate_outputs};
 use crate::application::ports::ModelCatalogPort;
 use crate::domain::errors::{DomainError, DomainResult};
-use crate::domain::model::ModelId;
+use crate::domain::model::{ModelId, ModelInfo, ModelTask};
 
 pub struct OnnxModelCatalog;
 
@@ -13,13 +16,39 @@ impl OnnxModelCatalog {
 
 #[async_trait]
 impl ModelCatalogPort for OnnxModelCatalog {
-    async fn validate_model(&self, model: &ModelId) -> DomainResult<()> {
+    async fn validate_model(&self, model: &ModelId, task: ModelTask) -> DomainResult<ModelInfo> {
         if model.onnx_path.trim().is_empty() {
             return Err(DomainError::InvalidInput("onnx_path empty".into()));
         }
         if !Path::new(&model.onnx_path).exists() {
             return Err(DomainError::NotFound(format!("model file not found: {}", model.onnx_path)));
         }
-        Ok(())
+
+        // Un modelo que ONNX Runtime no puede abrir se rechaza aquí, antes de tocar el pipeline.
+        // Sin hash: la validación no lo necesita y evita leer el fichero entero otra vez.
+        let path = model.onnx_path.clone();
+        let info = tokio::task::spawn_blocking(move || probe_model(&path))
+            .await
+            .map_err(|e| DomainError::OperationFailed(e.to_string()))?
+            .map_err(|e| DomainError::InvalidInput(format!("no se pudo inspeccionar el modelo {}: {e}", model.onnx_path)))?;
+        let task = resolve_task(task, info.metadata.get("task").map(String::as_str), &info.output_ranks());
+        validate_outputs(task, &info.output_ranks())
+            .map_err(|e| DomainError::InvalidInput(format!("modelo {} no válido: {e}", model.onnx_path)))?;
+        Ok(info)
+    }
+
+    async fn inspect_model(&self, model: &ModelId) -> DomainResult<ModelInfo> {
+        if !Path::new(&model.onnx_path).exists() {
+            return Err(DomainError::NotFound(format!("model file not found: {}", model.onnx_path)));
+        }
+        let path = model.onnx_path.clone();
+        tokio::task::spawn_blocking(move || inspect_model(&path))
+            .await
+            .map_err(|e| DomainError::OperationFailed(e.to_string()))?
+            .map_err(|e| DomainError::InvalidInput(format!("no se pudo inspeccionar el modelo {}: {e}", model.onnx_path)))
+    }
+
+    fn resolve_labels(&self, model: &ModelId, info: &ModelInfo) -> Vec<String> {
+        labels_from_names(&model.onnx_path, info.metadata.get("names").map(String::as_str))
     }
 }
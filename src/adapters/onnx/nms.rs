@@ -0,0 +1,84 @@
+use crate::adapters::onnx::obb::rotated_iou;
+use crate::domain::detection::Detection;
+use crate::domain::geometry::box_iou;
+
+/// Intersección sobre unión de dos cajas alineadas a los ejes.
+pub fn iou(a: &Detection, b: &Detection) -> f32 {
+    box_iou([a.x1, a.y1, a.x2, a.y2], [b.x1, b.y1, b.x2, b.y2])
+}
+
+/// Solape entre detecciones: IoU rotado si ambas traen caja orientada, IoU normal si no.
+fn overlap(a: &Detection, b: &Detection) -> f32 {
+    match (&a.obb, &b.obb) {
+        (Some(ra), Some(rb)) => rotated_iou(ra, rb),
+        _ => iou(a, b),
+    }
+}
+
+/// Supresión de no-máximos greedy.
+/// Por defecto solo compite entre cajas de la misma clase; con `agnostic`
+/// una caja suprime a cualquier otra que la solape, sea cual sea su clase.
+/// Devuelve los elementos supervivientes ordenados por score descendente.
+pub fn non_max_suppression<T: AsRef<Detection>>(mut items: Vec<T>, iou_threshold: f32, agnostic: bool) -> Vec<T> {
+    items.sort_unstable_by(|a, b| b.as_ref().score.total_cmp(&a.as_ref().score));
+
+    let mut kept: Vec<T> = Vec::with_capacity(items.len());
+    for item in items {
+        let det = item.as_ref();
+        let suppressed = kept.iter().any(|k| {
+            let k = k.as_ref();
+            (agnostic || k.class_id == det.class_id) && overlap(k, det) > iou_threshold
+        });
+        if !suppressed {
+            kept.push(item);
+        }
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn det(x: f32, score: f32, class_id: usize) -> Detection {
+        Detection {
+            x1: x,
+            y1: 0.0,
+            x2: x + 100.0,
+            y2: 100.0,
+            score,
+            class_id,
+            label: String::new(),
+            mask: None,
+            keypoints: Vec::new(),
+            obb: None,
+            attributes: Default::default(),
+            track_id: None,
+            track_age: None,
+        }
+    }
+
+    fn scores(kept: &[Detection]) -> Vec<f32> {
+        kept.iter().map(|d| d.score).collect()
+    }
+
+    #[test]
+    fn suppresses_overlapping_boxes_of_same_class() {
+        let kept = non_max_suppression(vec![det(0.0, 0.8, 0), det(10.0, 0.9, 0), det(300.0, 0.7, 0)], 0.45, false);
+        assert_eq!(scores(&kept), vec![0.9, 0.7]);
+    }
+
+    #[test]
+    fn keeps_overlapping_boxes_of_different_classes_unless_agnostic() {
+        let items = vec![det(0.0, 0.9, 0), det(10.0, 0.8, 1)];
+        assert_eq!(scores(&non_max_suppression(items.clone(), 0.45, false)), vec![0.9, 0.8]);
+        assert_eq!(scores(&non_max_suppression(items, 0.45, true)), vec![0.9]);
+    }
+
+    #[test]
+    fn keeps_boxes_below_iou_threshold() {
+        // IoU = 50 / 150
+        let kept = non_max_suppression(vec![det(0.0, 0.9, 0), det(50.0, 0.8, 0)], 0.45, false);
+        assert_eq!(kept.len(), 2);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::adapters::onnx::decoder::Candidate;
+use crate::adapters::onnx::preprocess::FrameTransform;
+use crate::domain::detection::RotatedBox;
+use crate::domain::geometry::{clip_polygon, polygon_area};
+
+/// Convierte un candidato de un modelo OBB (ángulo en el primer valor extra, en radianes)
+/// en una caja rotada sobre el frame, y ajusta `x1..y2` a la caja envolvente.
+///
+/// Con letterbox la escala es uniforme y la conversión es exacta; con stretch se escalan
+/// ancho y alto por separado, lo que es una aproximación para ángulos no rectos.
+pub fn apply_rotation(candidate: &mut Candidate, transform: &FrameTransform) {
+    let Some(&angle) = candidate.extra.first() else {
+        return;
+    };
+
+    let [x1, y1, x2, y2] = candidate.input_box;
+    let (cx, cy) = transform.project_to_frame((x1 + x2) / 2.0, (y1 + y2) / 2.0);
+    let rbox = RotatedBox {
+        cx,
+        cy,
+        w: (x2 - x1) / transform.scale_x,
+        h: (y2 - y1) / transform.scale_y,
+        angle,
+    };
+
+    let corners = rbox.corners();
+    let det = &mut candidate.detection;
+    det.x1 = corners.iter().map(|p| p[0]).fold(f32::INFINITY, f32::min).clamp(0.0, transform.frame_w);
+    det.y1 = corners.iter().map(|p| p[1]).fold(f32::INFINITY, f32::min).clamp(0.0, transform.frame_h);
+    det.x2 = corners.iter().map(|p| p[0]).fold(f32::NEG_INFINITY, f32::max).clamp(0.0, transform.frame_w);
+    det.y2 = corners.iter().map(|p| p[1]).fold(f32::NEG_INFINITY, f32::max).clamp(0.0, transform.frame_h);
+    det.obb = Some(rbox);
+}
+
+/// Intersección sobre unión de dos cajas rotadas (recorte de polígonos convexos).
+pub fn rotated_iou(a: &RotatedBox, b: &RotatedBox) -> f32 {
+    let pa = a.corners();
+    let pb = b.corners();
+    let inter = polygon_area(&clip_polygon(&pa, &pb));
+    let union = a.w * a.h + b.w * b.h - inter;
+    if union <= 0.0 { 0.0 } else { inter / union }
+}
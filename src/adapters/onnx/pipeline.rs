@@ -1,22 +1,48 @@
 use async_trait::async_trait;
-use std::sync::{Arc, RwLock};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::time::Instant;
 use tokio::sync::broadcast;
 use tracing::{error, info, warn};
 
-use crate::application::ports::StreamPort;
+use crate::application::ports::{AnalyticsPort, StreamPort};
 use crate::application::speech_service::SpeechService; 
 use crate::domain::{
+    analytics::{CountingLine, LineCounts, Zone, ZoneStats},
+    events::Event,
     camera::{CameraId, CameraMode},
     errors::{DomainError, DomainResult},
-    model::InferenceConfig,
+    model::{EngineStatus, InferenceConfig, ModelLoadState, ScheduleMode},
+    rules::Rule,
     stream::FrameMeta,
 };
 
 use crate::adapters::v4l2::capture::{CaptureConfig, V4l2Capture};
-use crate::adapters::onnx::yolo_engine::OnnxYoloEngine;
+use crate::adapters::analytics::line_counter::LineCounter;
+use crate::adapters::analytics::rule_engine::RuleEngine;
+use crate::adapters::analytics::snapshot_store::SnapshotStore;
+use crate::adapters::analytics::track_events::TrackEvents;
+use crate::adapters::analytics::tracker::Tracker;
+use crate::adapters::analytics::zone_monitor::ZoneMonitor;
+use crate::adapters::storage::rule_store::RuleStore;
+use crate::adapters::onnx::scheduler::{CapturedFrame, FrameSlot, InferenceGate};
+use crate::adapters::onnx::session_cache::{cache_key, SessionCache};
+use crate::adapters::onnx::yolo_engine::{InferenceOutput, OnnxYoloEngine};
 
 pub struct PipelineAdapter {
     cfg: Arc<RwLock<Option<PipelineConfig>>>,
+    /// Estado del modelo cargado actualmente.
+    status: Arc<RwLock<Option<EngineStatus>>>,
+    /// Carga de modelo en curso y último error.
+    load: Arc<RwLock<ModelLoadState>>,
+    /// Líneas de conteo, zonas y reglas por cámara (ruta del dispositivo).
+    analytics: Arc<Mutex<HashMap<String, CameraAnalytics>>>,
+    /// Fichero donde se guardan las reglas para que sobrevivan a un reinicio.
+    rule_store: RuleStore,
+    /// Eventos de las reglas y de los tracks, en un canal aparte del de frames.
+    events_tx: broadcast::Sender<Event>,
+    /// Recortes enlazados desde los eventos de los tracks.
+    snapshots: Arc<SnapshotStore>,
     tx: broadcast::Sender<(FrameMeta, Vec<u8>)>,
 }
 
@@ -24,19 +50,41 @@ pub struct PipelineAdapter {
 struct PipelineConfig { 
     camera: CameraId, 
     mode: CameraMode, 
-    infer: InferenceConfig 
+    infer: InferenceConfig,
+    /// Crece con cada `configure`, aunque la configuración sea idéntica; así
+    /// volver a aplicarla reintenta una carga que falló.
+    revision: u64,
 }
 
 impl PipelineAdapter {
     pub fn new() -> Self {
         let (tx, _) = broadcast::channel(16);
+        let (events_tx, _) = broadcast::channel(64);
         
         // Capturamos el handle de Tokio para que el SpeechService 
         // pueda realizar peticiones HTTP asíncronas a Ollama.
         let tokio_handle = tokio::runtime::Handle::current();
 
+        // Las reglas guardadas se aplican al arrancar; si el fichero no se puede leer se empieza sin ellas.
+        let rule_store = RuleStore::from_env();
+        let mut analytics = HashMap::new();
+        match rule_store.load() {
+            Ok(saved) => {
+                for (camera, rules) in saved {
+                    analytics.entry(camera).or_insert_with(CameraAnalytics::default).rules.set_rules(rules);
+                }
+            }
+            Err(e) => warn!("No se pudieron leer las reglas de {}: {:#}", rule_store.path().display(), e),
+        }
+
         let adapter = Self { 
             cfg: Arc::new(RwLock::new(None)), 
+            status: Arc::new(RwLock::new(None)),
+            load: Arc::new(RwLock::new(ModelLoadState::default())),
+            analytics: Arc::new(Mutex::new(analytics)),
+            rule_store,
+            events_tx,
+            snapshots: Arc::new(SnapshotStore::default()),
             tx 
         };
         
@@ -46,18 +94,39 @@ impl PipelineAdapter {
 
     fn spawn_worker(&self, tokio_handle: tokio::runtime::Handle) {
         let cfg_handle = self.cfg.clone();
+        let status_handle = self.status.clone();
+        let load_handle = self.load.clone();
+        let analytics = self.analytics.clone();
+        let events_tx = self.events_tx.clone();
+        let snapshots = self.snapshots.clone();
         let tx = self.tx.clone();
 
+        // La captura corre en su propio hilo y deja los frames en `slot`; el resultado
+        // de la última inferencia queda en `latest` para acompañar a los frames siguientes.
+        let slot = Arc::new(FrameSlot::default());
+        let latest: Arc<Mutex<Option<LatestResult>>> = Arc::new(Mutex::new(None));
+        Self::spawn_capture(cfg_handle.clone(), slot.clone(), latest.clone(), tx.clone());
+
         std::thread::spawn(move || {
             // Inicializamos el servicio de voz (intervalo de 12 segundos entre narraciones)
             let speech_service = SpeechService::new(12, tokio_handle);
             
-            let mut capture: Option<V4l2Capture> = None;
             let mut engine: Option<OnnxYoloEngine> = None;
-            let mut last_key: Option<String> = None;
+            // Clave de caché del motor que sirve, para guardarlo al sustituirlo.
+            let mut engine_cache_key: Option<String> = None;
+            let cache = Arc::new(Mutex::new(SessionCache::new(0)));
+            // Modelo pedido más recientemente (cargado o en carga) y el del motor que sirve.
+            let mut model_key: Option<String> = None;
+            let mut engine_key: Option<String> = None;
+            // Última carga fallida y revisión de la configuración que la pidió: no se
+            // reintenta en cada frame, solo cuando se vuelve a aplicar la configuración.
+            let mut failed_load: Option<(String, u64)> = None;
+            let mut load_revision = 0;
+            let mut tracker: Option<Tracker> = None;
+            let mut track_events: Option<TrackEvents> = None;
+            let (loaded_tx, loaded_rx) = mpsc::channel::<LoadedEngine>();
             
-            let mut fps_est: f32 = 0.0;
-            let mut last_t = std::time::Instant::now();
+            let mut fps = FpsEstimator::new();
 
             info!("Pipeline Worker: Hilo de procesamiento y visión iniciado.");
 
@@ -73,16 +142,177 @@ impl PipelineAdapter {
                     continue;
                 };
 
-                // 2. Comprobar si hay cambios en cámara o modelo
-                let config_key = format!("{}-{}-{}", 
-                    current.camera.path, 
-                    current.mode.size.width, 
-                    current.infer.model.onnx_path
+                // 2. Comprobar si hay cambios en el modelo
+                cache.lock().unwrap().set_capacity(current.infer.session_cache);
+
+                // El modelo nuevo se carga en otro hilo; el actual sigue sirviendo hasta el cambio.
+                let infer_key = format!("{}-{:?}-{:?}-{:?}-{:?}", 
+                    current.infer.model.onnx_path,
+                    current.infer.task,
+                    current.infer.runtime,
+                    current.infer.preprocess,
+                    current.infer.secondary
                 );
+                // Si se vuelve al modelo que ya sirve, el error de la carga abandonada deja de aplicar.
+                if failed_load.is_some() && Some(&infer_key) == engine_key.as_ref() {
+                    failed_load = None;
+                    load_handle.write().unwrap().last_error = None;
+                }
+                let failed = failed_load.as_ref().is_some_and(|(key, rev)| *key == infer_key && *rev == current.revision);
+                if Some(&infer_key) != model_key.as_ref() && !failed {
+                    info!("Pipeline: Cargando modelo en segundo plano {}", infer_key);
+                    load_handle.write().unwrap().loading = true;
+                    spawn_engine_load(infer_key.clone(), current.infer.clone(), cache.clone(), loaded_tx.clone());
+                    model_key = Some(infer_key);
+                    load_revision = current.revision;
+                }
+                while let Ok(loaded) = loaded_rx.try_recv() {
+                    // Una carga superada por otra configuración posterior no se usa, pero se guarda.
+                    if Some(&loaded.key) != model_key.as_ref() {
+                        if let Ok(eng) = loaded.result {
+                            cache.lock().unwrap().put(loaded.cache_key, eng);
+                        }
+                        continue;
+                    }
+                    let mut load = load_handle.write().unwrap();
+                    load.loading = false;
+                    match loaded.result {
+                        Ok(eng) => {
+                            info!("Pipeline: modelo cargado con proveedor {:?}", eng.provider());
+                            *status_handle.write().unwrap() = Some(EngineStatus {
+                                model_path: current.infer.model.onnx_path.clone(),
+                                provider: eng.provider(),
+                                preprocess: eng.preprocess().clone(),
+                                fixed_input: eng.fixed_input().map(|(w, h)| [w, h]),
+                            });
+                            if let (Some(old), Some(old_key)) = (engine.replace(eng), engine_cache_key.take()) {
+                                cache.lock().unwrap().put(old_key, old);
+                            }
+                            engine_cache_key = Some(loaded.cache_key);
+                            engine_key = Some(loaded.key);
+                            failed_load = None;
+                            load.last_error = None;
+                        }
+                        Err(e) => {
+                            error!("Error cargando modelo YOLO (se mantiene el anterior): {:?}", e);
+                            load.last_error = Some(format!("{e:#}"));
+                            // Volvemos a la clave del motor que sirve para que la carga pueda reintentarse.
+                            failed_load = Some((loaded.key, load_revision));
+                            model_key = engine_key.clone();
+                        }
+                    }
+                }
 
-                if Some(config_key.clone()) != last_key {
-                    info!("Pipeline: Recargando recursos para {}", config_key);
-                    
+                // 3. Inferencia sobre el frame más reciente que haya dejado el hilo de captura
+                let Some(frame) = slot.take(std::time::Duration::from_millis(250)) else {
+                    continue;
+                };
+                let t_infer_start = std::time::Instant::now();
+
+                // Inferencia YOLO para obtener cajas y etiquetas (o el top-k si es un clasificador)
+                // Sin modelo cargado aún, o si la inferencia falla, el frame se envía sin detecciones.
+                let mut output = engine.as_mut()
+                    .and_then(|eng| eng.infer(&frame.rgb, &current.infer.params)
+                        .map_err(|e| warn!("Error de inferencia: {:#}", e))
+                        .ok())
+                    .unwrap_or_default();
+
+                // Tracker entre la inferencia y el envío; empieza de cero si cambian sus opciones,
+                // y con él los eventos de los tracks, que dependen de sus identificadores.
+                let tracker_options = &current.infer.tracker;
+                if !tracker_options.enabled {
+                    tracker = None;
+                    track_events = None;
+                } else if tracker.as_ref().is_none_or(|t| t.options() != tracker_options) {
+                    tracker = Some(Tracker::new(tracker_options.clone()));
+                    track_events = tracker_options.events.enabled.then(|| TrackEvents::new(tracker_options.events.clone()));
+                }
+                if let Some(tracker) = tracker.as_mut() {
+                    tracker.update(&mut output.detections);
+                    if let Some(track_events) = track_events.as_mut() {
+                        let path = &current.camera.path;
+                        let events = track_events.update(path, &output.detections, tracker.lost(), &frame.rgb, frame.captured_at, &snapshots);
+                        for event in events {
+                            let _ = events_tx.send(event);
+                        }
+                    }
+                }
+
+                let (line_counts, zones) = match analytics.lock().unwrap().get_mut(&current.camera.path) {
+                    Some(camera) => {
+                        camera.lines.update(&output.detections);
+                        camera.zones.update(&output.detections, frame.captured_at);
+                        let path = &current.camera.path;
+                        for event in camera.rules.evaluate(path, &output.detections, camera.zones.stats(), frame.captured_at) {
+                            info!("Evento de regla en {}: {:?}", path, event.kind);
+                            let _ = events_tx.send(event);
+                        }
+                        (camera.lines.counts().to_vec(), camera.zones.stats().to_vec())
+                    }
+                    None => Default::default(),
+                };
+
+                let result = InferenceResult {
+                    output,
+                    skeleton: engine.as_ref().map(|e| e.skeleton().to_vec()).unwrap_or_default(),
+                    infer_ms: t_infer_start.elapsed().as_secs_f32() * 1000.0,
+                    captured_at: frame.captured_at,
+                    line_counts,
+                    zones,
+                };
+
+                // --- MEJORA AVANZADA: PROCESAMIENTO VISUAL ---
+                // Enviamos las detecciones Y el frame JPEG al servicio de voz.
+                // Esto permite que el servicio use un VLM (Vision Language Model) 
+                // para ver qué está pasando realmente.
+                if engine.is_some() {
+                    speech_service.process_frame(result.output.detections.clone(), frame.jpeg.clone());
+                }
+
+                // 4. Con `every_frame` el stream va al ritmo de la inferencia y se envía el propio
+                // frame inferido; si no, el hilo de captura envía los frames con este resultado.
+                if current.infer.schedule.mode == ScheduleMode::EveryFrame {
+                    // Lo arrastrado de un modo anterior no debe reaparecer si se vuelve a él.
+                    *latest.lock().unwrap() = None;
+                    publish(&tx, frame, Some(&result), true, &mut fps);
+                } else {
+                    *latest.lock().unwrap() = Some(LatestResult { result, published: false });
+                }
+                slot.mark_processed();
+            }
+        });
+    }
+
+    /// Hilo de captura: lee la cámara a su ritmo, ofrece al modelo los frames que admite la
+    /// cadencia y, fuera de `every_frame`, envía cada frame con las últimas detecciones. Con
+    /// `every_frame` espera a que se procese cada frame antes de leer el siguiente.
+    fn spawn_capture(
+        cfg_handle: Arc<RwLock<Option<PipelineConfig>>>,
+        slot: Arc<FrameSlot>,
+        latest: Arc<Mutex<Option<LatestResult>>>,
+        tx: broadcast::Sender<(FrameMeta, Vec<u8>)>,
+    ) {
+        std::thread::spawn(move || {
+            let mut capture: Option<V4l2Capture> = None;
+            let mut camera_key: Option<String> = None;
+            let mut gate = InferenceGate::default();
+            let mut fps = FpsEstimator::new();
+
+            loop {
+                let current = {
+                    let lock = cfg_handle.read().unwrap();
+                    lock.clone()
+                };
+
+                let Some(current) = current else {
+                    std::thread::sleep(std::time::Duration::from_millis(250));
+                    continue;
+                };
+
+                // Cambiar solo el modelo no reabre la cámara.
+                let cam_key = format!("{}-{}", current.camera.path, current.mode.size.width);
+                if Some(&cam_key) != camera_key.as_ref() {
+                    info!("Pipeline: Abriendo cámara {}", cam_key);
                     capture = V4l2Capture::open(&CaptureConfig {
                         camera_path: current.camera.path.clone(),
                         fourcc: current.mode.format.clone(),
@@ -90,60 +320,158 @@ impl PipelineAdapter {
                         height: current.mode.size.height,
                         fps: current.mode.fps,
                     }).map_err(|e| error!("Error abriendo cámara: {:?}", e)).ok();
-
-                    engine = OnnxYoloEngine::load(&current.infer.model.onnx_path)
-                        .map_err(|e| error!("Error cargando modelo YOLO: {:?}", e)).ok();
-                    
-                    last_key = Some(config_key);
+                    camera_key = Some(cam_key);
                 }
 
-                // 3. Captura e Inferencia
-                if let (Some(cap), Some(eng)) = (capture.as_mut(), engine.as_mut()) {
-                    match cap.next_rgb_and_jpeg() {
-                        Ok((rgb, jpeg, w, h)) => {
-                            let t_infer_start = std::time::Instant::now();
-                            
-                            // Inferencia YOLO para obtener cajas y etiquetas
-                            let detections = eng.infer(&rgb, &current.infer.params)
-                                .unwrap_or_default();
-                            
-                            let infer_ms = t_infer_start.elapsed().as_secs_f32() * 1000.0;
-
-                            // --- MEJORA AVANZADA: PROCESAMIENTO VISUAL ---
-                            // Enviamos las detecciones Y el frame JPEG al servicio de voz.
-                            // Esto permite que el servicio use un VLM (Vision Language Model) 
-                            // para ver qué está pasando realmente.
-                            speech_service.process_frame(detections.clone(), jpeg.clone());
-
-                            // Cálculo de FPS para la interfaz
-                            let dt = last_t.elapsed().as_secs_f32().max(0.001);
-                            last_t = std::time::Instant::now();
-                            fps_est = 0.9 * fps_est + 0.1 * (1.0 / dt);
-
-                            let meta = FrameMeta { 
-                                width: w, 
-                                height: h, 
-                                infer_ms, 
-                                fps_est, 
-                                detections 
-                            };
-                            
-                            // 4. Enviar resultado al Dashboard vía WebSocket
-                            if tx.receiver_count() > 0 {
-                                let _ = tx.send((meta, jpeg));
-                            }
+                let Some(cap) = capture.as_mut() else {
+                    std::thread::sleep(std::time::Duration::from_millis(1000));
+                    continue;
+                };
+
+                match cap.next_rgb_and_jpeg() {
+                    Ok((rgb, jpeg, width, height)) => {
+                        let frame = CapturedFrame { rgb, jpeg, width, height, captured_at: Instant::now() };
+                        let schedule = &current.infer.schedule;
+                        if schedule.mode == ScheduleMode::EveryFrame {
+                            // Como el bucle síncrono original: no se lee (ni se decodifica) otro
+                            // frame hasta que el modelo termina con este, así se infieren todos.
+                            slot.put(frame);
+                            while !slot.wait_processed(std::time::Duration::from_millis(250))
+                                && cfg_handle.read().unwrap().as_ref().is_some_and(|c| c.infer.schedule.mode == ScheduleMode::EveryFrame)
+                            {}
+                            continue;
                         }
-                        Err(e) => {
-                            warn!("Error capturando frame: {}", e);
-                            std::thread::sleep(std::time::Duration::from_millis(10));
+                        if gate.admit(schedule, frame.captured_at) {
+                            slot.put(frame.clone());
                         }
+
+                        let (result, fresh) = match latest.lock().unwrap().as_mut() {
+                            Some(latest) => {
+                                let fresh = !latest.published;
+                                latest.published = true;
+                                (Some(latest.result.clone()), fresh)
+                            }
+                            None => (None, false),
+                        };
+                        publish(&tx, frame, result.as_ref(), fresh, &mut fps);
+                    }
+                    Err(e) => {
+                        warn!("Error capturando frame: {}", e);
+                        std::thread::sleep(std::time::Duration::from_millis(10));
                     }
-                } else {
-                    std::thread::sleep(std::time::Duration::from_millis(1000));
                 }
             }
         });
     }
+
+    fn lock_analytics(&self) -> DomainResult<std::sync::MutexGuard<'_, HashMap<String, CameraAnalytics>>> {
+        self.analytics.lock()
+            .map_err(|_| DomainError::OperationFailed("Lock de analítica fallido".into()))
+    }
+}
+
+/// Resultado de inferencia de un frame.
+#[derive(Clone)]
+struct InferenceResult {
+    output: InferenceOutput,
+    skeleton: Vec<[usize; 2]>,
+    infer_ms: f32,
+    /// Captura del frame inferido.
+    captured_at: Instant,
+    line_counts: Vec<LineCounts>,
+    zones: Vec<ZoneStats>,
+}
+
+/// Analítica configurada para una cámara.
+#[derive(Default)]
+struct CameraAnalytics {
+    lines: LineCounter,
+    zones: ZoneMonitor,
+    rules: RuleEngine,
+}
+
+/// Último resultado, compartido con el hilo de captura.
+struct LatestResult {
+    result: InferenceResult,
+    /// Ya ha salido con algún frame: los siguientes lo llevan como arrastrado.
+    published: bool,
+}
+
+/// Envía un frame al dashboard con el resultado de inferencia indicado.
+fn publish(
+    tx: &broadcast::Sender<(FrameMeta, Vec<u8>)>,
+    frame: CapturedFrame,
+    result: Option<&InferenceResult>,
+    fresh: bool,
+    fps: &mut FpsEstimator,
+) {
+    let fps_est = fps.tick();
+    if tx.receiver_count() == 0 {
+        return;
+    }
+    let meta = FrameMeta {
+        width: frame.width,
+        height: frame.height,
+        infer_ms: result.map_or(0.0, |r| r.infer_ms),
+        fps_est,
+        detections: result.map(|r| r.output.detections.clone()).unwrap_or_default(),
+        skeleton: result.map(|r| r.skeleton.clone()).unwrap_or_default(),
+        classifications: result.map(|r| r.output.classifications.clone()).unwrap_or_default(),
+        tiling: result.and_then(|r| r.output.tiling.clone()),
+        fresh,
+        line_counts: result.map(|r| r.line_counts.clone()).unwrap_or_default(),
+        zones: result.map(|r| r.zones.clone()).unwrap_or_default(),
+        detections_age_ms: result.map_or(0.0, |r| frame.captured_at.duration_since(r.captured_at).as_secs_f32() * 1000.0),
+    };
+    let _ = tx.send((meta, frame.jpeg));
+}
+
+/// Media móvil de los FPS de envío, para la interfaz.
+struct FpsEstimator {
+    fps: f32,
+    last: Instant,
+}
+
+impl FpsEstimator {
+    fn new() -> Self {
+        Self { fps: 0.0, last: Instant::now() }
+    }
+
+    fn tick(&mut self) -> f32 {
+        let dt = self.last.elapsed().as_secs_f32().max(0.001);
+        self.last = Instant::now();
+        self.fps = 0.9 * self.fps + 0.1 * (1.0 / dt);
+        self.fps
+    }
+}
+
+/// Resultado de una carga en segundo plano.
+struct LoadedEngine {
+    /// Configuración que la pidió.
+    key: String,
+    cache_key: String,
+    result: anyhow::Result<OnnxYoloEngine>,
+}
+
+/// Recupera el motor de la caché o lo carga y calienta en un hilo aparte; el resultado vuelve por `tx`.
+fn spawn_engine_load(key: String, infer: InferenceConfig, cache: Arc<Mutex<SessionCache<OnnxYoloEngine>>>, tx: mpsc::Sender<LoadedEngine>) {
+    std::thread::spawn(move || {
+        let cache_key = cache_key(&infer);
+        let cached = cache.lock().unwrap().take(&cache_key);
+        let result = match cached {
+            Some(engine) => {
+                info!("Pipeline: modelo recuperado de la caché de sesiones");
+                Ok(engine)
+            }
+            None => OnnxYoloEngine::load(&infer).and_then(|mut engine| {
+                let t_warmup = std::time::Instant::now();
+                engine.warmup(&infer.params)?;
+                info!("Pipeline: warmup en {:.1} ms", t_warmup.elapsed().as_secs_f32() * 1000.0);
+                Ok(engine)
+            }),
+        };
+        let _ = tx.send(LoadedEngine { key, cache_key, result });
+    });
 }
 
 #[async_trait]
@@ -151,11 +479,107 @@ impl StreamPort for PipelineAdapter {
     async fn configure(&self, camera: CameraId, mode: CameraMode, infer: InferenceConfig) -> DomainResult<()> {
         let mut lock = self.cfg.write()
             .map_err(|_| DomainError::OperationFailed("Lock de configuración fallido".into()))?;
-        *lock = Some(PipelineConfig { camera, mode, infer });
+        let revision = lock.as_ref().map_or(0, |c| c.revision + 1);
+        *lock = Some(PipelineConfig { camera, mode, infer, revision });
         Ok(())
     }
 
     async fn subscribe(&self) -> DomainResult<broadcast::Receiver<(FrameMeta, Vec<u8>)>> {
         Ok(self.tx.subscribe())
     }
+
+    async fn current_config(&self) -> DomainResult<Option<(CameraId, CameraMode, InferenceConfig)>> {
+        let lock = self.cfg.read()
+            .map_err(|_| DomainError::OperationFailed("Lock de configuración fallido".into()))?;
+        Ok(lock.clone().map(|c| (c.camera, c.mode, c.infer)))
+    }
+
+    async fn engine_status(&self) -> DomainResult<Option<EngineStatus>> {
+        let lock = self.status.read()
+            .map_err(|_| DomainError::OperationFailed("Lock de estado fallido".into()))?;
+        Ok(lock.clone())
+    }
+
+    async fn model_load_state(&self) -> DomainResult<ModelLoadState> {
+        let lock = self.load.read()
+            .map_err(|_| DomainError::OperationFailed("Lock de estado fallido".into()))?;
+        Ok(lock.clone())
+    }
+}
+
+#[async_trait]
+impl AnalyticsPort for PipelineAdapter {
+    async fn set_lines(&self, camera: &CameraId, lines: Vec<CountingLine>) -> DomainResult<()> {
+        let mut analytics = self.lock_analytics()?;
+        analytics.entry(camera.path.clone()).or_default().lines.set_lines(lines);
+        Ok(())
+    }
+
+    async fn lines(&self, camera: &CameraId) -> DomainResult<Vec<CountingLine>> {
+        let analytics = self.lock_analytics()?;
+        Ok(analytics.get(&camera.path).map(|a| a.lines.lines().to_vec()).unwrap_or_default())
+    }
+
+    async fn line_counts(&self, camera: &CameraId) -> DomainResult<Vec<LineCounts>> {
+        let analytics = self.lock_analytics()?;
+        Ok(analytics.get(&camera.path).map(|a| a.lines.counts().to_vec()).unwrap_or_default())
+    }
+
+    async fn set_zones(&self, camera: &CameraId, zones: Vec<Zone>) -> DomainResult<()> {
+        let mut analytics = self.lock_analytics()?;
+        analytics.entry(camera.path.clone()).or_default().zones.set_zones(zones);
+        Ok(())
+    }
+
+    async fn zones(&self, camera: &CameraId) -> DomainResult<Vec<Zone>> {
+        let analytics = self.lock_analytics()?;
+        Ok(analytics.get(&camera.path).map(|a| a.zones.zones().to_vec()).unwrap_or_default())
+    }
+
+    async fn zone_stats(&self, camera: &CameraId) -> DomainResult<Vec<ZoneStats>> {
+        let analytics = self.lock_analytics()?;
+        Ok(analytics.get(&camera.path).map(|a| a.zones.stats().to_vec()).unwrap_or_default())
+    }
+
+    async fn set_rules(&self, camera: &CameraId, rules: Vec<Rule>) -> DomainResult<()> {
+        let mut analytics = self.lock_analytics()?;
+        // Se guarda antes de aplicar: si falla la escritura, las reglas en uso no cambian.
+        let mut saved: BTreeMap<String, Vec<Rule>> = analytics
+            .iter()
+            .filter(|(_, a)| !a.rules.rules().is_empty())
+            .map(|(path, a)| (path.clone(), a.rules.rules().to_vec()))
+            .collect();
+        if rules.is_empty() {
+            saved.remove(&camera.path);
+        } else {
+            saved.insert(camera.path.clone(), rules.clone());
+        }
+        self.rule_store
+            .save(&saved)
+            .map_err(|e| DomainError::OperationFailed(format!("no se pudieron guardar las reglas: {e:#}")))?;
+        analytics.entry(camera.path.clone()).or_default().rules.set_rules(rules);
+        Ok(())
+    }
+
+    async fn rules(&self, camera: &CameraId) -> DomainResult<Vec<Rule>> {
+        let analytics = self.lock_analytics()?;
+        Ok(analytics.get(&camera.path).map(|a| a.rules.rules().to_vec()).unwrap_or_default())
+    }
+
+    async fn subscribe_events(&self) -> DomainResult<broadcast::Receiver<Event>> {
+        Ok(self.events_tx.subscribe())
+    }
+
+    async fn snapshot(&self, id: u64) -> DomainResult<Option<Vec<u8>>> {
+        Ok(self.snapshots.get(id).map(|jpeg| jpeg.to_vec()))
+    }
+
+    async fn reset_counts(&self, camera: &CameraId) -> DomainResult<()> {
+        let mut analytics = self.lock_analytics()?;
+        if let Some(camera) = analytics.get_mut(&camera.path) {
+            camera.lines.reset();
+            camera.zones.reset();
+        }
+        Ok(())
+    }
 }
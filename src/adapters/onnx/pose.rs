@@ -0,0 +1,68 @@
+use crate::adapters::onnx::preprocess::FrameTransform;
+use crate::domain::detection::Keypoint;
+
+/// Esqueleto COCO de 17 puntos (índices base 0), el que usa Ultralytics para YOLO-pose.
+pub const COCO_SKELETON: [[usize; 2]; 19] = [
+    [15, 13], [13, 11], [16, 14], [14, 12], [11, 12], [5, 11], [6, 12], [5, 6], [5, 7], [6, 8],
+    [7, 9], [8, 10], [1, 2], [0, 1], [0, 2], [1, 3], [2, 4], [3, 5], [4, 6],
+];
+
+/// Forma de los keypoints declarada por el modelo: (número de puntos, valores por punto).
+/// Los valores por punto son 2 (x, y) o 3 (x, y, visibilidad).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeypointShape {
+    pub count: usize,
+    pub dims: usize,
+}
+
+impl KeypointShape {
+    pub fn values_per_instance(&self) -> usize {
+        self.count * self.dims
+    }
+}
+
+/// Interpreta la entrada `kpt_shape` de los metadatos, p.ej. `[17, 3]`.
+pub fn parse_kpt_shape(raw: &str) -> Option<KeypointShape> {
+    match parse_ints(raw).as_slice() {
+        [count, dims] if *count > 0 && (*dims == 2 || *dims == 3) => Some(KeypointShape { count: *count, dims: *dims }),
+        _ => None,
+    }
+}
+
+/// Interpreta la entrada `skeleton` de los metadatos: lista de pares `[[a, b], ...]`.
+pub fn parse_skeleton(raw: &str) -> Option<Vec<[usize; 2]>> {
+    let ints = parse_ints(raw);
+    if ints.is_empty() || !ints.len().is_multiple_of(2) {
+        return None;
+    }
+    Some(ints.chunks_exact(2).map(|p| [p[0], p[1]]).collect())
+}
+
+/// Esqueleto por defecto para la forma dada: COCO si son 17 puntos, ninguno en otro caso.
+pub fn default_skeleton(shape: KeypointShape) -> Vec<[usize; 2]> {
+    if shape.count == 17 { COCO_SKELETON.to_vec() } else { Vec::new() }
+}
+
+/// Convierte los valores extra de un candidato en keypoints sobre el frame original.
+pub fn decode_keypoints(extra: &[f32], shape: KeypointShape, transform: &FrameTransform) -> Vec<Keypoint> {
+    extra
+        .chunks_exact(shape.dims)
+        .take(shape.count)
+        .map(|kp| {
+            let (x, y) = transform.project_to_frame(kp[0], kp[1]);
+            Keypoint {
+                x,
+                y,
+                visibility: if shape.dims == 3 { kp[2] } else { 1.0 },
+            }
+        })
+        .collect()
+}
+
+/// Extrae los enteros no negativos de una lista en texto, p.ej. `[17, 3]`.
+pub fn parse_ints(raw: &str) -> Vec<usize> {
+    raw.split(|c: char| !c.is_ascii_digit())
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| s.parse().ok())
+        .collect()
+}
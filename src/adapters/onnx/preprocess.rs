@@ -0,0 +1,161 @@
+use image::{imageops::FilterType, Rgb, RgbImage};
+
+use crate::domain::model::{ChannelOrder, PreprocessSpec, ResizeFilter, ResizeMode, TensorLayout};
+
+/// Transformación aplicada al frame para llevarlo al tamaño de entrada del modelo.
+/// Permite deshacerla para devolver las cajas a coordenadas del frame original.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameTransform {
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub pad_x: f32,
+    pub pad_y: f32,
+    pub frame_w: f32,
+    pub frame_h: f32,
+}
+
+impl FrameTransform {
+    /// Convierte un punto del espacio de entrada del modelo al espacio del frame.
+    pub fn project_to_frame(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            ((x - self.pad_x) / self.scale_x).clamp(0.0, self.frame_w),
+            ((y - self.pad_y) / self.scale_y).clamp(0.0, self.frame_h),
+        )
+    }
+}
+
+/// Stride de las exportaciones YOLO cuando los metadatos no lo declaran.
+pub const DEFAULT_STRIDE: u32 = 32;
+
+/// `(ancho, alto)` de una entrada de forma fija, leída de la forma declarada por la sesión
+/// (`-1` en los ejes dinámicos).
+pub fn fixed_input_size(input_shape: &[i64], layout: TensorLayout) -> Option<(u32, u32)> {
+    let (h, w) = match (layout, input_shape) {
+        (TensorLayout::Nchw, [_, _, h, w]) | (TensorLayout::Nhwc, [_, h, w, _]) => (*h, *w),
+        _ => return None,
+    };
+    (h > 0 && w > 0).then_some((w as u32, h as u32))
+}
+
+/// Tamaño de entrada `(ancho, alto)` para un modelo de ejes dinámicos.
+/// Con letterbox es el frame escalado para caber en `max` y redondeado al múltiplo de
+/// `stride` (el rectángulo mínimo, sin relleno sobrante); con stretch, `max` alineado a `stride`.
+pub fn dynamic_input_size(frame: (u32, u32), max: (u32, u32), mode: ResizeMode, stride: u32) -> (u32, u32) {
+    let stride = stride.max(1);
+    let align = |v: u32| v.div_ceil(stride).max(1) * stride;
+
+    match mode {
+        ResizeMode::Stretch => (align(max.0), align(max.1)),
+        ResizeMode::Letterbox => {
+            let r = (max.0 as f32 / frame.0 as f32).min(max.1 as f32 / frame.1 as f32);
+            (align((frame.0 as f32 * r).round() as u32), align((frame.1 as f32 * r).round() as u32))
+        }
+    }
+}
+
+/// Redimensiona el frame a `size = (ancho, alto)` según el modo indicado,
+/// con el filtro y el color de relleno del preprocesado.
+pub fn resize_to_input(rgb: &RgbImage, size: (u32, u32), mode: ResizeMode, spec: &PreprocessSpec) -> (RgbImage, FrameTransform) {
+    let (w, h) = (rgb.width(), rgb.height());
+    let (in_w, in_h) = size;
+    let filter = |default: FilterType| match spec.filter {
+        ResizeFilter::Auto => default,
+        ResizeFilter::Nearest => FilterType::Nearest,
+        ResizeFilter::Bilinear => FilterType::Triangle,
+        ResizeFilter::Bicubic => FilterType::CatmullRom,
+        ResizeFilter::Lanczos => FilterType::Lanczos3,
+    };
+
+    match mode {
+        ResizeMode::Stretch => {
+            let resized = image::imageops::resize(rgb, in_w, in_h, filter(FilterType::Nearest));
+            let t = FrameTransform {
+                scale_x: in_w as f32 / w as f32,
+                scale_y: in_h as f32 / h as f32,
+                pad_x: 0.0,
+                pad_y: 0.0,
+                frame_w: w as f32,
+                frame_h: h as f32,
+            };
+            (resized, t)
+        }
+        ResizeMode::Letterbox => {
+            // Escala única para conservar el aspecto; el sobrante se rellena con `pad_color`.
+            let r = (in_w as f32 / w as f32).min(in_h as f32 / h as f32);
+            let new_w = ((w as f32 * r).round() as u32).clamp(1, in_w);
+            let new_h = ((h as f32 * r).round() as u32).clamp(1, in_h);
+            let pad_x = (in_w - new_w) / 2;
+            let pad_y = (in_h - new_h) / 2;
+
+            let resized = image::imageops::resize(rgb, new_w, new_h, filter(FilterType::Triangle));
+            let mut canvas = RgbImage::from_pixel(in_w, in_h, Rgb(spec.pad_color));
+            image::imageops::replace(&mut canvas, &resized, pad_x as i64, pad_y as i64);
+
+            let t = FrameTransform {
+                scale_x: new_w as f32 / w as f32,
+                scale_y: new_h as f32 / h as f32,
+                pad_x: pad_x as f32,
+                pad_y: pad_y as f32,
+                frame_w: w as f32,
+                frame_h: h as f32,
+            };
+            (canvas, t)
+        }
+    }
+}
+
+/// Preprocesado declarado por el modelo. Parte del estándar de Ultralytics y aplica
+/// las entradas de metadatos presentes (`channel_order`, `layout`, `scale`, `mean`, `std`,
+/// `quant_scale`, `quant_zero_point`, `output_quant_scale`, `output_quant_zero_point`);
+/// sin `layout`, una entrada `[1, H, W, 3]` se toma como NHWC.
+pub fn spec_from_metadata(custom: impl Fn(&str) -> Option<String>, input_shape: &[i64]) -> PreprocessSpec {
+    let mut spec = PreprocessSpec::default();
+
+    if let [_, c, _, 3] = input_shape {
+        if *c != 3 {
+            spec.layout = TensorLayout::Nhwc;
+        }
+    }
+    match custom("layout").map(|v| v.to_ascii_lowercase()).as_deref() {
+        Some("nhwc") => spec.layout = TensorLayout::Nhwc,
+        Some("nchw") => spec.layout = TensorLayout::Nchw,
+        _ => {}
+    }
+    match custom("channel_order").map(|v| v.to_ascii_lowercase()).as_deref() {
+        Some("bgr") => spec.channel_order = ChannelOrder::Bgr,
+        Some("rgb") => spec.channel_order = ChannelOrder::Rgb,
+        _ => {}
+    }
+    if let Some(scale) = custom("scale").and_then(|v| v.trim().parse().ok()) {
+        spec.scale = scale;
+    }
+    if let Some(mean) = custom("mean").and_then(|v| parse_triplet(&v)) {
+        spec.mean = mean;
+    }
+    if let Some(std) = custom("std").and_then(|v| parse_triplet(&v)) {
+        spec.std = std;
+    }
+    if let Some(scale) = custom("quant_scale").and_then(|v| v.trim().parse().ok()) {
+        spec.quant_scale = scale;
+    }
+    if let Some(zero_point) = custom("quant_zero_point").and_then(|v| v.trim().parse().ok()) {
+        spec.quant_zero_point = Some(zero_point);
+    }
+    if let Some(scale) = custom("output_quant_scale").and_then(|v| v.trim().parse().ok()) {
+        spec.output_quant_scale = Some(scale);
+    }
+    if let Some(zero_point) = custom("output_quant_zero_point").and_then(|v| v.trim().parse().ok()) {
+        spec.output_quant_zero_point = zero_point;
+    }
+    spec
+}
+
+/// Interpreta una lista de tres reales, p.ej. `[0.485, 0.456, 0.406]`.
+fn parse_triplet(raw: &str) -> Option<[f32; 3]> {
+    let values: Vec<f32> = raw
+        .trim_matches(|c: char| c == '[' || c == ']' || c == '(' || c == ')' || c.is_whitespace())
+        .split(',')
+        .map(|v| v.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    values.try_into().ok()
+}
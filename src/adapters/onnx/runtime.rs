@@ -0,0 +1,88 @@
+use anyhow::{bail, Result};
+use ort::execution_providers::{
+    CoreMLExecutionProvider, CUDAExecutionProvider, DirectMLExecutionProvider, ExecutionProviderDispatch,
+    OpenVINOExecutionProvider, TensorRTExecutionProvider, XNNPACKExecutionProvider,
+};
+use ort::session::builder::{GraphOptimizationLevel, SessionBuilder};
+use ort::session::Session;
+use std::fs;
+use std::path::PathBuf;
+use tracing::warn;
+
+use crate::domain::model::{ExecutionProviderKind, GraphOptimization, RuntimeOptions};
+
+/// Prepara el builder de sesión con hilos, optimización, profiling y proveedores.
+///
+/// Los proveedores se registran en el orden pedido; los que no estén disponibles en
+/// esta máquina se omiten. Devuelve también el proveedor principal efectivo
+/// (el primero que se registró, o CPU si no lo hizo ninguno).
+pub fn session_builder(options: &RuntimeOptions) -> Result<(SessionBuilder, ExecutionProviderKind)> {
+    let mut builder = Session::builder()?.with_optimization_level(optimization_level(options.optimization))?;
+    if options.intra_threads > 0 {
+        builder = builder.with_intra_threads(options.intra_threads)?;
+    }
+    if options.inter_threads > 0 {
+        builder = builder.with_inter_threads(options.inter_threads)?;
+    }
+    if let Some(path) = &options.profiling_path {
+        builder = builder.with_profiling(profiling_prefix(options, path)?)?;
+    }
+
+    let mut active = None;
+    for &kind in &options.providers {
+        // CPU está siempre disponible: lo que venga detrás no llegaría a usarse.
+        let Some(provider) = dispatch(kind) else {
+            active.get_or_insert(kind);
+            break;
+        };
+        match builder.clone().with_execution_providers([provider.error_on_failure()]) {
+            Ok(with_provider) => {
+                builder = with_provider;
+                active.get_or_insert(kind);
+            }
+            Err(e) => warn!("Proveedor {:?} no disponible: {}", kind, e),
+        }
+    }
+
+    Ok((builder, active.unwrap_or(ExecutionProviderKind::Cpu)))
+}
+
+fn profiling_root() -> PathBuf {
+    std::env::var("PROFILING_ROOT")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("profiles"))
+}
+
+/// Prefijo de los ficheros de profiling dentro de `PROFILING_ROOT`, creando los directorios que falten.
+fn profiling_prefix(options: &RuntimeOptions, path: &str) -> Result<PathBuf> {
+    if !options.profiling_path_is_safe() {
+        bail!("profiling_path fuera de PROFILING_ROOT: {path}");
+    }
+    let prefix = profiling_root().join(path);
+    if let Some(dir) = prefix.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    Ok(prefix)
+}
+
+/// Proveedor de `ort` correspondiente; `None` para CPU, que no necesita registro.
+fn dispatch(kind: ExecutionProviderKind) -> Option<ExecutionProviderDispatch> {
+    match kind {
+        ExecutionProviderKind::Cpu => None,
+        ExecutionProviderKind::Cuda => Some(CUDAExecutionProvider::default().build()),
+        ExecutionProviderKind::TensorRt => Some(TensorRTExecutionProvider::default().build()),
+        ExecutionProviderKind::OpenVino => Some(OpenVINOExecutionProvider::default().build()),
+        ExecutionProviderKind::Xnnpack => Some(XNNPACKExecutionProvider::default().build()),
+        ExecutionProviderKind::CoreMl => Some(CoreMLExecutionProvider::default().build()),
+        ExecutionProviderKind::DirectMl => Some(DirectMLExecutionProvider::default().build()),
+    }
+}
+
+fn optimization_level(level: GraphOptimization) -> GraphOptimizationLevel {
+    match level {
+        GraphOptimization::Disable => GraphOptimizationLevel::Disable,
+        GraphOptimization::Basic => GraphOptimizationLevel::Level1,
+        GraphOptimization::Extended => GraphOptimizationLevel::Level2,
+        GraphOptimization::All => GraphOptimizationLevel::All,
+    }
+}
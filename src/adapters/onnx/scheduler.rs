@@ -0,0 +1,87 @@
+use image::RgbImage;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::domain::model::{InferenceSchedule, ScheduleMode};
+
+/// Frame tal y como sale de la cámara.
+#[derive(Clone)]
+pub struct CapturedFrame {
+    pub rgb: RgbImage,
+    pub jpeg: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub captured_at: Instant,
+}
+
+/// Hueco de un solo frame entre el hilo de captura y el de inferencia. Cada frame nuevo
+/// sustituye al que no se haya recogido aún, así el modelo trabaja siempre con el más reciente.
+#[derive(Default)]
+pub struct FrameSlot {
+    state: Mutex<SlotState>,
+    ready: Condvar,
+    processed: Condvar,
+}
+
+#[derive(Default)]
+struct SlotState {
+    frame: Option<CapturedFrame>,
+    /// Hay un frame entregado que el hilo de inferencia aún no ha terminado de procesar.
+    pending: bool,
+}
+
+impl FrameSlot {
+    pub fn put(&self, frame: CapturedFrame) {
+        let mut state = self.state.lock().unwrap();
+        state.frame = Some(frame);
+        state.pending = true;
+        self.ready.notify_one();
+    }
+
+    /// Espera como mucho `timeout` a que haya un frame y lo recoge.
+    pub fn take(&self, timeout: Duration) -> Option<CapturedFrame> {
+        let guard = self.state.lock().unwrap();
+        let (mut guard, _) = self.ready.wait_timeout_while(guard, timeout, |s| s.frame.is_none()).unwrap();
+        guard.frame.take()
+    }
+
+    /// El hilo de inferencia ha terminado con el último frame recogido.
+    pub fn mark_processed(&self) {
+        self.state.lock().unwrap().pending = false;
+        self.processed.notify_all();
+    }
+
+    /// Espera como mucho `timeout` a que se procese el último frame entregado; `true` si ya lo está.
+    pub fn wait_processed(&self, timeout: Duration) -> bool {
+        let guard = self.state.lock().unwrap();
+        let (guard, _) = self.processed.wait_timeout_while(guard, timeout, |s| s.pending).unwrap();
+        !guard.pending
+    }
+}
+
+/// Decide qué frames capturados se ofrecen al modelo según la cadencia configurada.
+#[derive(Default)]
+pub struct InferenceGate {
+    seen: u64,
+    last_admitted: Option<Instant>,
+}
+
+impl InferenceGate {
+    pub fn admit(&mut self, schedule: &InferenceSchedule, now: Instant) -> bool {
+        let admit = match schedule.mode {
+            ScheduleMode::EveryFrame | ScheduleMode::Latest => true,
+            ScheduleMode::EveryN => self.seen.is_multiple_of(u64::from(schedule.every_n.max(1))),
+            ScheduleMode::MaxHz => {
+                schedule.max_hz <= 0.0
+                    || self
+                        .last_admitted
+                        .is_none_or(|last| now.duration_since(last).as_secs_f32() >= 1.0 / schedule.max_hz)
+            }
+        };
+        self.seen += 1;
+        if admit {
+            self.last_admitted = Some(now);
+        }
+        admit
+    }
+}
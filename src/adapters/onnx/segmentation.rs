@@ -0,0 +1,105 @@
+use ndarray::ArrayView3;
+
+use crate::adapters::onnx::preprocess::FrameTransform;
+use crate::domain::detection::{Detection, InstanceMask};
+
+/// Reconstruye la máscara de una instancia a partir de sus coeficientes y de los
+/// prototipos `[nm, mh, mw]` de un modelo YOLO-seg.
+///
+/// La rejilla resultante es la zona útil de los prototipos (sin el relleno del
+/// letterbox), de modo que cubre el frame completo. Solo se activan las celdas
+/// dentro de la caja de la detección, como hace Ultralytics.
+pub fn decode_mask(
+    coeffs: &[f32],
+    protos: ArrayView3<f32>,
+    det: &Detection,
+    transform: &FrameTransform,
+    input_size: (u32, u32),
+) -> InstanceMask {
+    let (mh, mw) = (protos.shape()[1], protos.shape()[2]);
+    let rx = mw as f32 / input_size.0 as f32;
+    let ry = mh as f32 / input_size.1 as f32;
+
+    // Zona de los prototipos que corresponde al frame (sin relleno).
+    let off_x = (transform.pad_x * rx).round() as usize;
+    let off_y = (transform.pad_y * ry).round() as usize;
+    let grid_w = ((transform.frame_w * transform.scale_x * rx).round() as usize).clamp(1, mw - off_x.min(mw - 1));
+    let grid_h = ((transform.frame_h * transform.scale_y * ry).round() as usize).clamp(1, mh - off_y.min(mh - 1));
+
+    let cell_w = transform.frame_w / grid_w as f32;
+    let cell_h = transform.frame_h / grid_h as f32;
+    let u0 = (det.x1 / cell_w).floor().max(0.0) as usize;
+    let u1 = ((det.x2 / cell_w).ceil() as usize).min(grid_w);
+    let v0 = (det.y1 / cell_h).floor().max(0.0) as usize;
+    let v1 = ((det.y2 / cell_h).ceil() as usize).min(grid_h);
+
+    let nm = coeffs.len().min(protos.shape()[0]);
+    let mut bits = vec![false; grid_w * grid_h];
+    for v in v0..v1 {
+        for u in u0..u1 {
+            // sigmoid(x) > 0.5  <=>  x > 0
+            let logit: f32 = (0..nm).map(|k| coeffs[k] * protos[[k, v + off_y, u + off_x]]).sum();
+            bits[v * grid_w + u] = logit > 0.0;
+        }
+    }
+
+    InstanceMask {
+        width: grid_w as u32,
+        height: grid_h as u32,
+        counts: encode_rle(&bits),
+    }
+}
+
+/// Traslada la máscara de un recorte `(x, y, ancho, alto)` a una rejilla que cubre el frame
+/// `(ancho, alto)`, con el mismo tamaño de celda que la original.
+pub fn place_mask(mask: &InstanceMask, crop: (u32, u32, u32, u32), frame: (u32, u32)) -> InstanceMask {
+    let (crop_x, crop_y, crop_w, crop_h) = crop;
+    let cell_w = crop_w as f32 / mask.width.max(1) as f32;
+    let cell_h = crop_h as f32 / mask.height.max(1) as f32;
+    let grid_w = ((frame.0 as f32 / cell_w).round() as usize).max(1);
+    let grid_h = ((frame.1 as f32 / cell_h).round() as usize).max(1);
+    let off_u = (crop_x as f32 / cell_w).round() as usize;
+    let off_v = (crop_y as f32 / cell_h).round() as usize;
+
+    let src = decode_rle(&mask.counts, (mask.width * mask.height) as usize);
+    let mut bits = vec![false; grid_w * grid_h];
+    for (i, _) in src.iter().enumerate().filter(|(_, &b)| b) {
+        let u = i % mask.width as usize + off_u;
+        let v = i / mask.width as usize + off_v;
+        if u < grid_w && v < grid_h {
+            bits[v * grid_w + u] = true;
+        }
+    }
+
+    InstanceMask {
+        width: grid_w as u32,
+        height: grid_h as u32,
+        counts: encode_rle(&bits),
+    }
+}
+
+fn decode_rle(counts: &[u32], len: usize) -> Vec<bool> {
+    let mut bits = Vec::with_capacity(len);
+    for (i, &run) in counts.iter().enumerate() {
+        bits.extend(std::iter::repeat_n(i % 2 == 1, run as usize));
+    }
+    bits.resize(len, false);
+    bits
+}
+
+/// RLE por filas que empieza siempre por un tramo de ceros (posiblemente vacío).
+fn encode_rle(bits: &[bool]) -> Vec<u32> {
+    let mut counts = Vec::new();
+    let mut current = false;
+    let mut run = 0u32;
+    for &b in bits {
+        if b != current {
+            counts.push(run);
+            current = b;
+            run = 0;
+        }
+        run += 1;
+    }
+    counts.push(run);
+    counts
+}
@@ -0,0 +1,59 @@
+use std::collections::VecDeque;
+use std::fs;
+
+use crate::domain::model::InferenceConfig;
+
+/// Motores ya cargados y calentados que no están sirviendo, para volver a ellos sin
+/// leer el modelo de disco. Se descarta el menos usado al superar la capacidad.
+pub struct SessionCache<T> {
+    capacity: usize,
+    /// Del menos al más reciente.
+    entries: VecDeque<(String, T)>,
+}
+
+impl<T> SessionCache<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, entries: VecDeque::new() }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    /// Saca el motor de la caché (pasa a ser el que sirve).
+    pub fn take(&mut self, key: &str) -> Option<T> {
+        let pos = self.entries.iter().position(|(k, _)| k == key)?;
+        self.entries.remove(pos).map(|(_, v)| v)
+    }
+
+    /// Guarda un motor retirado como el más reciente.
+    pub fn put(&mut self, key: String, value: T) {
+        self.entries.retain(|(k, _)| *k != key);
+        self.entries.push_back((key, value));
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+}
+
+/// Clave de caché: todo lo que fija el motor al cargarlo, más la fecha de modificación de
+/// cada `.onnx` para no reutilizar una sesión de un fichero que se ha sobrescrito.
+pub fn cache_key(infer: &InferenceConfig) -> String {
+    let mtime = |path: &str| fs::metadata(path).and_then(|m| m.modified()).ok();
+    let secondary_mtimes: Vec<_> = infer.secondary.iter().map(|s| mtime(&s.onnx_path)).collect();
+    format!(
+        "{}-{:?}-{:?}-{:?}-{:?}-{:?}-{:?}",
+        infer.model.onnx_path,
+        mtime(&infer.model.onnx_path),
+        infer.task,
+        infer.runtime,
+        infer.preprocess,
+        infer.secondary,
+        secondary_mtimes
+    )
+}
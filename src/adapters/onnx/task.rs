@@ -0,0 +1,50 @@
+use ort::session::Session;
+
+use crate::domain::model::ModelTask;
+
+/// Determina la tarea efectiva del modelo: la pedida en la configuración o,
+/// con `Auto`, la declarada en los metadatos (`task`) o deducida de sus salidas.
+pub fn resolve_task(requested: ModelTask, declared: Option<&str>, output_ranks: &[usize]) -> ModelTask {
+    if requested != ModelTask::Auto {
+        return requested;
+    }
+
+    match declared {
+        Some("detect") => ModelTask::Detect,
+        Some("segment") => ModelTask::Segment,
+        Some("pose") => ModelTask::Pose,
+        Some("obb") => ModelTask::Obb,
+        Some("classify") => ModelTask::Classify,
+        _ => match output_ranks {
+            [2, ..] => ModelTask::Classify,
+            [3, 4, ..] => ModelTask::Segment,
+            _ => ModelTask::Detect,
+        },
+    }
+}
+
+/// Comprueba que las salidas del modelo tienen la forma que espera la tarea.
+pub fn validate_outputs(task: ModelTask, output_ranks: &[usize]) -> Result<(), String> {
+    let ok = match task {
+        ModelTask::Classify => matches!(output_ranks, [2, ..]),
+        ModelTask::Segment => matches!(output_ranks, [3, 4, ..]),
+        ModelTask::Detect | ModelTask::Pose | ModelTask::Obb | ModelTask::Auto => matches!(output_ranks, [3, ..]),
+    };
+
+    if ok {
+        Ok(())
+    } else {
+        Err(format!(
+            "las salidas del modelo (rangos {output_ranks:?}) no corresponden a la tarea {task:?}"
+        ))
+    }
+}
+
+/// Número de dimensiones de cada salida tensorial de la sesión, en orden.
+pub fn output_ranks(session: &Session) -> Vec<usize> {
+    session
+        .outputs()
+        .iter()
+        .filter_map(|o| o.dtype().tensor_shape().map(|s| s.len()))
+        .collect()
+}
@@ -0,0 +1,401 @@
+use anyhow::{bail, Result};
+use half::f16;
+use image::RgbImage;
+use ort::session::Session;
+use ort::tensor::TensorElementType;
+use ort::value::{DynValue, Value};
+use std::borrow::Cow;
+
+use crate::domain::model::{ChannelOrder, PreprocessSpec, TensorLayout};
+
+/// Tipo de elemento con el que el modelo espera la imagen de entrada.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputElement {
+    F32,
+    F16,
+    U8,
+    I8,
+}
+
+impl InputElement {
+    /// Lee el tipo de la primera entrada de la sesión.
+    pub fn from_session(session: &Session) -> Result<Self> {
+        let Some(input) = session.inputs().first() else {
+            bail!("el modelo no declara entradas");
+        };
+        match input.dtype().tensor_type() {
+            Some(TensorElementType::Float32) => Ok(Self::F32),
+            Some(TensorElementType::Float16) => Ok(Self::F16),
+            Some(TensorElementType::Uint8) => Ok(Self::U8),
+            Some(TensorElementType::Int8) => Ok(Self::I8),
+            other => bail!("tipo de entrada no soportado: {:?}", other),
+        }
+    }
+}
+
+/// Datos de entrada ya convertidos al tipo del modelo, en el orden del tensor.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputData {
+    F32(Vec<f32>),
+    F16(Vec<f16>),
+    U8(Vec<u8>),
+    I8(Vec<i8>),
+}
+
+/// Construye el tensor de entrada (`[1, 3, H, W]` o `[1, H, W, 3]`) en el tipo que pide el modelo.
+pub fn image_to_tensor(img: &RgbImage, element: InputElement, spec: &PreprocessSpec) -> Result<DynValue> {
+    let (w, h) = (img.width() as i64, img.height() as i64);
+    let shape = match spec.layout {
+        TensorLayout::Nchw => vec![1, 3, h, w],
+        TensorLayout::Nhwc => vec![1, h, w, 3],
+    };
+
+    let value = match input_data(img, element, spec) {
+        InputData::F32(data) => Value::from_array((shape, data))?.into_dyn(),
+        InputData::F16(data) => Value::from_array((shape, data))?.into_dyn(),
+        InputData::U8(data) => Value::from_array((shape, data))?.into_dyn(),
+        InputData::I8(data) => Value::from_array((shape, data))?.into_dyn(),
+    };
+    Ok(value)
+}
+
+/// Convierte la imagen al tipo de entrada. Las entradas en coma flotante se normalizan según
+/// `spec`; las enteras se cuantizan como `q = round(pixel / quant_scale) + zero_point`, con
+/// zero-point 0 en u8 (el píxel crudo) y -128 en i8 (el píxel centrado) si no se indica otro,
+/// que es lo que esperan las exportaciones habituales con entrada entera.
+pub fn input_data(img: &RgbImage, element: InputElement, spec: &PreprocessSpec) -> InputData {
+    let normalize = |v: u8, c: usize| (v as f32 * spec.scale - spec.mean[c]) / spec.std[c];
+    let quantize = |v: u8, default_zero_point: i32, min: f32, max: f32| {
+        let zero_point = spec.quant_zero_point.unwrap_or(default_zero_point) as f32;
+        ((v as f32 / spec.quant_scale).round() + zero_point).clamp(min, max)
+    };
+
+    match element {
+        InputElement::F32 => InputData::F32(arrange(img, spec, normalize)),
+        InputElement::F16 => InputData::F16(arrange(img, spec, |v, c| f16::from_f32(normalize(v, c)))),
+        InputElement::U8 => InputData::U8(arrange(img, spec, |v, _| quantize(v, 0, 0.0, 255.0) as u8)),
+        InputElement::I8 => InputData::I8(arrange(img, spec, |v, _| quantize(v, -128, -128.0, 127.0) as i8)),
+    }
+}
+
+/// Recoloca los píxeles RGB intercalados según el orden de canales y la disposición
+/// del modelo, convirtiendo cada valor con `convert(valor, canal_de_salida)`.
+fn arrange<T: Copy + Default>(img: &RgbImage, spec: &PreprocessSpec, convert: impl Fn(u8, usize) -> T) -> Vec<T> {
+    let plane = (img.width() * img.height()) as usize;
+    let source = match spec.channel_order {
+        ChannelOrder::Rgb => [0, 1, 2],
+        ChannelOrder::Bgr => [2, 1, 0],
+    };
+
+    let mut data = vec![T::default(); 3 * plane];
+    for (i, pixel) in img.pixels().enumerate() {
+        for (c, &src) in source.iter().enumerate() {
+            let index = match spec.layout {
+                TensorLayout::Nchw => c * plane + i,
+                TensorLayout::Nhwc => i * 3 + c,
+            };
+            data[index] = convert(pixel[src], c);
+        }
+    }
+    data
+}
+
+/// Comprueba que todas las salidas se pueden leer como f32. Las salidas enteras (u8/i8) de un
+/// modelo cuantizado solo se aceptan si `spec` indica cómo descuantizarlas.
+pub fn check_outputs(session: &Session, spec: &PreprocessSpec) -> Result<()> {
+    for output in session.outputs() {
+        check_output_type(output.name(), output.dtype().tensor_type(), spec)?;
+    }
+    Ok(())
+}
+
+fn check_output_type(name: &str, element: Option<TensorElementType>, spec: &PreprocessSpec) -> Result<()> {
+    match element {
+        Some(TensorElementType::Float32 | TensorElementType::Float16) => Ok(()),
+        Some(TensorElementType::Uint8 | TensorElementType::Int8) if spec.output_quant_scale.is_some() => Ok(()),
+        Some(TensorElementType::Uint8 | TensorElementType::Int8) => bail!(
+            "la salida {} es entera ({:?}) y no se sabe cómo descuantizarla; indica \
+             output_quant_scale/output_quant_zero_point en preprocess o en los metadatos del modelo",
+            name,
+            element.unwrap()
+        ),
+        other => bail!("tipo de salida no soportado en {}: {:?}", name, other),
+    }
+}
+
+/// Extrae una salida como f32 (f32 sin copia; f16 convertido; u8/i8 descuantizado con `spec`).
+pub fn extract_f32<'a>(value: &'a DynValue, spec: &PreprocessSpec) -> Result<(Vec<usize>, Cow<'a, [f32]>)> {
+    let dims = |shape: &[i64]| shape.iter().map(|&d| d as usize).collect::<Vec<_>>();
+    let element = value.dtype().tensor_type();
+
+    match element {
+        Some(TensorElementType::Float32) => {
+            let (shape, data) = value.try_extract_tensor::<f32>()?;
+            Ok((dims(shape), Cow::Borrowed(data)))
+        }
+        Some(TensorElementType::Float16) => {
+            let (shape, data) = value.try_extract_tensor::<f16>()?;
+            Ok((dims(shape), Cow::Owned(widen_f16(data))))
+        }
+        Some(TensorElementType::Uint8) => {
+            check_output_type("del modelo", element, spec)?;
+            let (shape, data) = value.try_extract_tensor::<u8>()?;
+            Ok((dims(shape), Cow::Owned(dequantize(data.iter().map(|&q| q as i32), spec))))
+        }
+        Some(TensorElementType::Int8) => {
+            check_output_type("del modelo", element, spec)?;
+            let (shape, data) = value.try_extract_tensor::<i8>()?;
+            Ok((dims(shape), Cow::Owned(dequantize(data.iter().map(|&q| q as i32), spec))))
+        }
+        other => {
+            check_output_type("del modelo", other, spec)?;
+            bail!("tipo de salida no soportado: {:?}", other)
+        }
+    }
+}
+
+fn widen_f16(data: &[f16]) -> Vec<f32> {
+    data.iter().map(|v| v.to_f32()).collect()
+}
+
+fn dequantize(data: impl Iterator<Item = i32>, spec: &PreprocessSpec) -> Vec<f32> {
+    let scale = spec.output_quant_scale.unwrap_or(1.0);
+    data.map(|q| (q - spec.output_quant_zero_point) as f32 * scale).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Imagen 2x1 con píxeles (0, 128, 255) y (10, 20, 30).
+    fn image() -> RgbImage {
+        let mut img = RgbImage::new(2, 1);
+        img.put_pixel(0, 0, image::Rgb([0, 128, 255]));
+        img.put_pixel(1, 0, image::Rgb([10, 20, 30]));
+        img
+    }
+
+    #[test]
+    fn arranges_layout_and_channel_order() {
+        let mut spec = PreprocessSpec::default();
+        assert_eq!(arrange(&image(), &spec, |v, _| v), vec![0, 10, 128, 20, 255, 30]);
+        spec.layout = TensorLayout::Nhwc;
+        assert_eq!(arrange(&image(), &spec, |v, _| v), vec![0, 128, 255, 10, 20, 30]);
+        spec.channel_order = ChannelOrder::Bgr;
+        assert_eq!(arrange(&image(), &spec, |v, _| v), vec![255, 128, 0, 30, 20, 10]);
+        assert_eq!(arrange(&image(), &spec, |_, c| c), vec![0, 1, 2, 0, 1, 2]);
+    }
+
+    #[test]
+    fn f32_input_is_normalized_per_channel() {
+        let spec = PreprocessSpec { scale: 1.0, mean: [0.0, 100.0, 200.0], std: [1.0, 2.0, 5.0], ..Default::default() };
+        let InputData::F32(data) = input_data(&image(), InputElement::F32, &spec) else { panic!("se esperaba f32") };
+        assert_eq!(data, vec![0.0, 10.0, 14.0, -40.0, 11.0, -34.0]);
+    }
+
+    #[test]
+    fn f16_input_round_trips_through_output_conversion() {
+        let spec = PreprocessSpec::default();
+        let InputData::F16(data) = input_data(&image(), InputElement::F16, &spec) else { panic!("se esperaba f16") };
+        let InputData::F32(expected) = input_data(&image(), InputElement::F32, &spec) else { unreachable!() };
+        for (value, expected) in widen_f16(&data).iter().zip(&expected) {
+            assert!((value - expected).abs() < 1e-3, "{value} != {expected}");
+        }
+    }
+
+    #[test]
+    fn u8_input_keeps_raw_pixels_by_default() {
+        let data = input_data(&image(), InputElement::U8, &PreprocessSpec::default());
+        assert_eq!(data, InputData::U8(vec![0, 10, 128, 20, 255, 30]));
+    }
+
+    #[test]
+    fn i8_input_is_centered_by_default() {
+        let data = input_data(&image(), InputElement::I8, &PreprocessSpec::default());
+        assert_eq!(data, InputData::I8(vec![-128, -118, 0, -108, 127, -98]));
+    }
+
+    #[test]
+    fn integer_input_uses_configured_quantization() {
+        let spec = PreprocessSpec { quant_scale: 2.0, quant_zero_point: Some(-100), ..Default::default() };
+        assert_eq!(input_data(&image(), InputElement::I8, &spec), InputData::I8(vec![-100, -95, -36, -90, 28, -85]));
+        let spec = PreprocessSpec { quant_scale: 0.5, quant_zero_point: Some(3), ..Default::default() };
+        // Se satura en vez de desbordar.
+        assert_eq!(input_data(&image(), InputElement::U8, &spec), InputData::U8(vec![3, 23, 255, 43, 255, 63]));
+    }
+
+    #[test]
+    fn integer_outputs_need_quantization_parameters() {
+        let spec = PreprocessSpec::default();
+        assert!(check_output_type("output0", Some(TensorElementType::Float32), &spec).is_ok());
+        assert!(check_output_type("output0", Some(TensorElementType::Float16), &spec).is_ok());
+        for element in [TensorElementType::Uint8, TensorElementType::Int8] {
+            let err = check_output_type("output0", Some(element), &spec).unwrap_err().to_string();
+            assert!(err.contains("output0") && err.contains("output_quant_scale"), "{err}");
+        }
+        assert!(check_output_type("output0", Some(TensorElementType::Int64), &spec).is_err());
+
+        let spec = PreprocessSpec { output_quant_scale: Some(0.5), ..Default::default() };
+        assert!(check_output_type("output0", Some(TensorElementType::Uint8), &spec).is_ok());
+        assert!(check_output_type("output0", Some(TensorElementType::Int8), &spec).is_ok());
+    }
+
+    #[test]
+    fn integer_outputs_are_dequantized() {
+        let spec = PreprocessSpec { output_quant_scale: Some(0.5), output_quant_zero_point: -128, ..Default::default() };
+        assert_eq!(dequantize([-128, 0, 127].into_iter(), &spec), vec![0.0, 64.0, 127.5]);
+    }
+
+    /// Ida y vuelta por ONNX Runtime con modelos `Identity` generados al vuelo, uno por tipo:
+    /// tipo de entrada leído de la sesión, tensor construido, ejecución y extracción a f32.
+    mod with_session {
+        use super::*;
+
+        // Códigos de `TensorProto.DataType`.
+        const FLOAT: u64 = 1;
+        const UINT8: u64 = 2;
+        const INT8: u64 = 3;
+        const FLOAT16: u64 = 10;
+
+        fn varint(out: &mut Vec<u8>, mut v: u64) {
+            while v >= 0x80 {
+                out.push((v as u8) | 0x80);
+                v >>= 7;
+            }
+            out.push(v as u8);
+        }
+
+        fn int_field(out: &mut Vec<u8>, field: u64, v: u64) {
+            varint(out, field << 3);
+            varint(out, v);
+        }
+
+        fn bytes_field(out: &mut Vec<u8>, field: u64, data: &[u8]) {
+            varint(out, (field << 3) | 2);
+            varint(out, data.len() as u64);
+            out.extend_from_slice(data);
+        }
+
+        /// `ValueInfoProto` de un tensor con forma fija.
+        fn value_info(name: &str, elem_type: u64, shape: &[u64]) -> Vec<u8> {
+            let mut dims = Vec::new();
+            for &d in shape {
+                let mut dim = Vec::new();
+                int_field(&mut dim, 1, d);
+                bytes_field(&mut dims, 1, &dim);
+            }
+            let mut tensor = Vec::new();
+            int_field(&mut tensor, 1, elem_type);
+            bytes_field(&mut tensor, 2, &dims);
+            let mut type_proto = Vec::new();
+            bytes_field(&mut type_proto, 1, &tensor);
+            let mut info = Vec::new();
+            bytes_field(&mut info, 1, name.as_bytes());
+            bytes_field(&mut info, 2, &type_proto);
+            info
+        }
+
+        /// `ModelProto` con un único nodo `Identity` de `x` a `y`.
+        fn identity_model(elem_type: u64, shape: &[u64]) -> Vec<u8> {
+            let mut node = Vec::new();
+            bytes_field(&mut node, 1, b"x");
+            bytes_field(&mut node, 2, b"y");
+            bytes_field(&mut node, 4, b"Identity");
+            let mut graph = Vec::new();
+            bytes_field(&mut graph, 1, &node);
+            bytes_field(&mut graph, 2, b"identity");
+            bytes_field(&mut graph, 11, &value_info("x", elem_type, shape));
+            bytes_field(&mut graph, 12, &value_info("y", elem_type, shape));
+            let mut opset = Vec::new();
+            int_field(&mut opset, 2, 13);
+            let mut model = Vec::new();
+            int_field(&mut model, 1, 8);
+            bytes_field(&mut model, 7, &graph);
+            bytes_field(&mut model, 8, &opset);
+            model
+        }
+
+        fn round_trip(elem_type: u64, expected: InputElement, spec: &PreprocessSpec) -> Vec<f32> {
+            let img = RgbImage::from_fn(2, 2, |x, y| image::Rgb([(x * 100) as u8, (y * 200) as u8, 255]));
+            let shape = match spec.layout {
+                TensorLayout::Nchw => [1, 3, 2, 2],
+                TensorLayout::Nhwc => [1, 2, 2, 3],
+            };
+            let mut session = Session::builder()
+                .and_then(|b| b.commit_from_memory(&identity_model(elem_type, &shape)))
+                .expect("modelo Identity generado");
+
+            let element = InputElement::from_session(&session).unwrap();
+            assert_eq!(element, expected);
+            check_outputs(&session, spec).unwrap();
+
+            let input = image_to_tensor(&img, element, spec).unwrap();
+            let outputs = session.run(ort::inputs![input]).unwrap();
+            let (dims, data) = extract_f32(&outputs[0], spec).unwrap();
+            assert_eq!(dims, shape.map(|d| d as usize));
+
+            // La salida de Identity es la entrada: debe coincidir con los datos construidos.
+            let fed: Vec<f32> = match input_data(&img, element, spec) {
+                InputData::F32(d) => d,
+                InputData::F16(d) => widen_f16(&d),
+                InputData::U8(d) => d.iter().map(|&q| q as f32).collect(),
+                InputData::I8(d) => d.iter().map(|&q| q as f32).collect(),
+            };
+            assert_eq!(data.to_vec(), fed);
+            data.to_vec()
+        }
+
+        /// Identidad en las salidas enteras: el valor extraído es el propio `q`.
+        fn raw_outputs() -> PreprocessSpec {
+            PreprocessSpec { output_quant_scale: Some(1.0), ..Default::default() }
+        }
+
+        #[test]
+        fn f32_model_round_trip() {
+            let data = round_trip(FLOAT, InputElement::F32, &PreprocessSpec::default());
+            assert_eq!(data[0], 0.0);
+            assert!((data[1] - 100.0 / 255.0).abs() < 1e-6);
+        }
+
+        #[test]
+        fn f16_model_round_trip() {
+            let spec = PreprocessSpec { layout: TensorLayout::Nhwc, ..Default::default() };
+            let data = round_trip(FLOAT16, InputElement::F16, &spec);
+            assert!((data[2] - 1.0).abs() < 1e-3);
+        }
+
+        #[test]
+        fn u8_model_round_trip() {
+            let data = round_trip(UINT8, InputElement::U8, &raw_outputs());
+            assert_eq!(&data[..4], &[0.0, 100.0, 0.0, 100.0]);
+        }
+
+        #[test]
+        fn i8_model_round_trip() {
+            let data = round_trip(INT8, InputElement::I8, &raw_outputs());
+            assert_eq!(&data[..4], &[-128.0, -28.0, -128.0, -28.0]);
+        }
+
+        #[test]
+        fn integer_output_is_dequantized_after_run() {
+            let spec = PreprocessSpec { output_quant_scale: Some(0.5), output_quant_zero_point: 10, ..Default::default() };
+            let img = RgbImage::from_pixel(2, 2, image::Rgb([30, 30, 30]));
+            let mut session = Session::builder()
+                .and_then(|b| b.commit_from_memory(&identity_model(UINT8, &[1, 3, 2, 2])))
+                .unwrap();
+            let input = image_to_tensor(&img, InputElement::U8, &spec).unwrap();
+            let outputs = session.run(ort::inputs![input]).unwrap();
+            let (_, data) = extract_f32(&outputs[0], &spec).unwrap();
+            // q = 30 / 1 + 0 (entrada) y valor = (30 - 10) * 0.5 (salida).
+            assert!(data.iter().all(|&v| v == 10.0));
+        }
+
+        #[test]
+        fn integer_output_without_scale_is_rejected() {
+            let session = Session::builder()
+                .and_then(|b| b.commit_from_memory(&identity_model(INT8, &[1, 3, 2, 2])))
+                .unwrap();
+            assert!(check_outputs(&session, &PreprocessSpec::default()).is_err());
+        }
+    }
+}
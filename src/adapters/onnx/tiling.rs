@@ -0,0 +1,60 @@
+use crate::adapters::onnx::segmentation::place_mask;
+use crate::domain::detection::Detection;
+
+/// Recorte del frame en píxeles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Rejilla de recortes de `tile` (ancho, alto) que cubre el frame con el solape pedido.
+/// Los últimos recortes de cada eje se ajustan al borde en lugar de salirse del frame;
+/// si el frame no es mayor que un recorte devuelve un único recorte con el frame completo.
+pub fn tile_grid(frame: (u32, u32), tile: (u32, u32), overlap: f32) -> Vec<Tile> {
+    let overlap = overlap.clamp(0.0, 0.9);
+    let xs = axis_starts(frame.0, tile.0, overlap);
+    let ys = axis_starts(frame.1, tile.1, overlap);
+    ys.iter()
+        .flat_map(|&y| {
+            xs.iter().map(move |&x| Tile {
+                x,
+                y,
+                width: tile.0.clamp(1, frame.0),
+                height: tile.1.clamp(1, frame.1),
+            })
+        })
+        .collect()
+}
+
+fn axis_starts(len: u32, size: u32, overlap: f32) -> Vec<u32> {
+    if size == 0 || size >= len {
+        return vec![0];
+    }
+    let step = ((size as f32 * (1.0 - overlap)).round() as u32).max(1);
+    let mut starts: Vec<u32> = (0..len - size).step_by(step as usize).collect();
+    starts.push(len - size);
+    starts
+}
+
+/// Lleva una detección hecha sobre `tile` a coordenadas del frame.
+pub fn to_frame(det: &mut Detection, tile: Tile, frame: (u32, u32)) {
+    let (dx, dy) = (tile.x as f32, tile.y as f32);
+    det.x1 += dx;
+    det.x2 += dx;
+    det.y1 += dy;
+    det.y2 += dy;
+    for kp in &mut det.keypoints {
+        kp.x += dx;
+        kp.y += dy;
+    }
+    if let Some(obb) = &mut det.obb {
+        obb.cx += dx;
+        obb.cy += dy;
+    }
+    if let Some(mask) = &det.mask {
+        det.mask = Some(place_mask(mask, (tile.x, tile.y, tile.width, tile.height), frame));
+    }
+}
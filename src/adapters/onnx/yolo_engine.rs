@@ -1,101 +1,299 @@
 use anyhow::Result;
-use image::{imageops::FilterType, RgbImage};
-use ndarray::{s, Array4, ArrayViewD, Axis, IxDyn};
-use ort::execution_providers::CUDAExecutionProvider;
+use image::RgbImage;
+use ndarray::{ArrayView4, ArrayViewD, Axis, Ix2, IxDyn};
 use ort::session::Session;
-use ort::value::Value;
 use std::fs;
+use std::time::Instant;
 
-use crate::domain::detection::Detection;
-use crate::domain::model::YoloParams;
+use crate::adapters::onnx::class_filter::ClassRules;
+use crate::adapters::onnx::cascade::SecondaryStage;
+use crate::adapters::onnx::class_map::ClassRemap;
+use crate::adapters::onnx::classification::top_k;
+use crate::adapters::onnx::decoder::{finalize_candidates, select_decoder, DecodeContext};
+use crate::adapters::onnx::labels::resolve_labels;
+use crate::adapters::onnx::nms::non_max_suppression;
+use crate::adapters::onnx::obb::apply_rotation;
+use crate::adapters::onnx::pose::{decode_keypoints, default_skeleton, parse_ints, parse_kpt_shape, parse_skeleton, KeypointShape};
+use crate::adapters::onnx::preprocess::{
+    dynamic_input_size, fixed_input_size, resize_to_input, spec_from_metadata, DEFAULT_STRIDE,
+};
+use crate::adapters::onnx::runtime::session_builder;
+use crate::adapters::onnx::segmentation::decode_mask;
+use crate::adapters::onnx::task::{output_ranks, resolve_task};
+use crate::adapters::onnx::tiling::{tile_grid, to_frame};
+use crate::adapters::onnx::tensor_io::{check_outputs, extract_f32, image_to_tensor, InputElement};
+use crate::domain::detection::{Classification, Detection};
+use crate::domain::model::{ExecutionProviderKind, InferenceConfig, ModelTask, PreprocessSpec, YoloParams};
+use crate::domain::stream::TileStats;
+
+/// Resultado de una inferencia: detecciones o, en modelos de clasificación, el top-k del frame.
+#[derive(Debug, Clone, Default)]
+pub struct InferenceOutput {
+    pub detections: Vec<Detection>,
+    pub classifications: Vec<Classification>,
+    /// Recortes y tiempos si el frame se procesó por recortes.
+    pub tiling: Option<TileStats>,
+}
 
 pub struct OnnxYoloEngine {
     session: Session,
+    /// Proveedor de ejecución principal con el que se creó la sesión.
+    provider: ExecutionProviderKind,
+    labels: Vec<String>,
+    /// Tipo de la entrada del modelo (f32, f16 o cuantizada).
+    input_element: InputElement,
+    /// Preprocesado efectivo (el de la configuración o el declarado por el modelo).
+    preprocess: PreprocessSpec,
+    /// (ancho, alto) de la entrada si el modelo la fija; `None` si sus ejes son dinámicos.
+    fixed_input: Option<(u32, u32)>,
+    /// Múltiplo al que se alinea una entrada dinámica (`stride` de los metadatos).
+    stride: u32,
+    /// Tarea efectiva, ya resuelta (nunca `Auto`).
+    task: ModelTask,
+    /// El modelo declara en sus metadatos que ya incluye NMS (`end2end`).
+    end2end: bool,
+    /// Forma de los keypoints en modelos de pose (`kpt_shape`).
+    keypoints: Option<KeypointShape>,
+    skeleton: Vec<[usize; 2]>,
+    /// Modelos secundarios que se aplican sobre los recortes de las detecciones.
+    secondary: Vec<SecondaryStage>,
 }
 
 impl OnnxYoloEngine {
-    pub fn load(path: &str) -> Result<Self> {
-        let mut builder = Session::builder()?.with_intra_threads(4)?;
-
-        // CUDA es opcional: si está disponible se registra, si no continuamos en CPU.
-        let cuda = CUDAExecutionProvider::default().build();
-        if let Ok(builder_with_cuda) = builder.clone().with_execution_providers([cuda]) {
-            builder = builder_with_cuda;
-        }
+    pub fn load(config: &InferenceConfig) -> Result<Self> {
+        let model = &config.model;
+        let (builder, provider) = session_builder(&config.runtime)?;
 
         // Con `ort` sin default-features, usamos commit_from_memory.
-        let model_bytes = fs::read(path)?;
+        let model_bytes = fs::read(&model.onnx_path)?;
         let session = builder.commit_from_memory(&model_bytes)?;
 
-        Ok(Self { session })
+        // Si el catálogo ya resolvió las etiquetas las respetamos; si no, las leemos del modelo.
+        let labels = if model.labels.is_empty() {
+            resolve_labels(&model.onnx_path, &session)
+        } else {
+            model.labels.clone()
+        };
+
+        let input_shape = session
+            .inputs()
+            .first()
+            .and_then(|i| i.dtype().tensor_shape().map(|s| s.to_vec()))
+            .unwrap_or_default();
+
+        let (task, end2end, keypoints, skeleton, preprocess, stride) = {
+            let metadata = session.metadata().ok();
+            let custom = |key: &str| metadata.as_ref().and_then(|m| m.custom(key));
+
+            let task = resolve_task(config.task, custom("task").as_deref(), &output_ranks(&session));
+            let end2end = custom("end2end").is_some_and(|v| v.eq_ignore_ascii_case("true"));
+            // Sin `kpt_shape` asumimos el formato COCO de 17 puntos con visibilidad.
+            let keypoints = (task == ModelTask::Pose).then(|| {
+                custom("kpt_shape")
+                    .and_then(|v| parse_kpt_shape(&v))
+                    .unwrap_or(KeypointShape { count: 17, dims: 3 })
+            });
+            let skeleton = match keypoints {
+                Some(shape) => custom("skeleton")
+                    .and_then(|v| parse_skeleton(&v))
+                    .unwrap_or_else(|| default_skeleton(shape)),
+                None => Vec::new(),
+            };
+            let stride = custom("stride")
+                .and_then(|v| parse_ints(&v).into_iter().max())
+                .map_or(DEFAULT_STRIDE, |s| s as u32);
+            let preprocess = config.preprocess.clone().unwrap_or_else(|| spec_from_metadata(custom, &input_shape));
+            (task, end2end, keypoints, skeleton, preprocess, stride)
+        };
+        let fixed_input = fixed_input_size(&input_shape, preprocess.layout);
+
+        let input_element = InputElement::from_session(&session)?;
+        check_outputs(&session, &preprocess)?;
+        let secondary = config
+            .secondary
+            .iter()
+            .map(|spec| SecondaryStage::load(spec, &config.runtime))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            session,
+            provider,
+            labels,
+            input_element,
+            preprocess,
+            fixed_input,
+            stride,
+            task,
+            end2end,
+            keypoints,
+            skeleton,
+            secondary,
+        })
     }
 
-    pub fn infer(&mut self, rgb: &RgbImage, params: &YoloParams) -> Result<Vec<Detection>> {
-        let classes = [
-            "persona", "bicicleta", "coche", "motocicleta", "avión", "autobús", "tren", "camión", "barco",
-            "semáforo", "hidrante", "señal de stop", "parquímetro", "banco", "pájaro", "gato", "perro",
-            "caballo", "oveja", "vaca", "elefante", "oso", "cebra", "jirafa", "mochila", "paraguas",
-            "bolso", "corbata", "maleta", "frisbee", "esquís", "snowboard", "pelota", "cometa",
-            "bate de béisbol", "guante de béisbol", "monopatín", "tabla de surf", "raqueta de tenis",
-            "botella", "copa de vino", "taza", "tenedor", "cuchillo", "cuchara", "tazón", "plátano",
-            "manzana", "sándwich", "naranja", "brócoli", "zanahoria", "perrito caliente", "pizza",
-            "donut", "pastel", "silla", "sofá", "planta", "cama", "mesa", "inodoro", "televisor",
-            "portátil", "ratón", "mando", "teclado", "móvil", "microondas", "horno", "tostadora",
-            "fregadero", "nevera", "libro", "reloj", "jarrón", "tijeras", "peluche", "secador", "cepillo"
-        ];
-
-        let imgsz = params.input_size as usize;
-        let resized = image::imageops::resize(rgb, imgsz as u32, imgsz as u32, FilterType::Nearest);
-
-        let mut input = Array4::<f32>::zeros((1, 3, imgsz, imgsz));
-        for (x, y, pixel) in resized.enumerate_pixels() {
-            input[[0, 0, y as usize, x as usize]] = pixel[0] as f32 / 255.0;
-            input[[0, 1, y as usize, x as usize]] = pixel[1] as f32 / 255.0;
-            input[[0, 2, y as usize, x as usize]] = pixel[2] as f32 / 255.0;
+    /// Inferencia sobre un frame vacío para que el primer frame real no pague la inicialización
+    /// perezosa del proveedor (reserva de memoria, autotuning de CUDA/TensorRT).
+    pub fn warmup(&mut self, params: &YoloParams) -> Result<()> {
+        let (w, h) = self.fixed_input.unwrap_or((params.input_width, params.input_height));
+        self.infer(&RgbImage::new(w.max(1), h.max(1)), params)?;
+        // Sobre un frame vacío no hay detecciones que lleguen a los modelos secundarios.
+        for stage in &mut self.secondary {
+            stage.warmup()?;
         }
+        Ok(())
+    }
 
-        let input_shape = vec![1, 3, imgsz as i64, imgsz as i64];
-        let input_tensor = Value::from_array((input_shape, input.into_raw_vec()))?;
+    /// (ancho, alto) de la entrada si el modelo la fija.
+    pub fn fixed_input(&self) -> Option<(u32, u32)> {
+        self.fixed_input
+    }
 
-        let outputs = self.session.run(ort::inputs![input_tensor])?;
-        let (shape_out, data_out) = outputs[0].try_extract_tensor::<f32>()?;
+    /// Preprocesado con el que se construye la entrada.
+    pub fn preprocess(&self) -> &PreprocessSpec {
+        &self.preprocess
+    }
+
+    pub fn provider(&self) -> ExecutionProviderKind {
+        self.provider
+    }
+
+    /// Pares de keypoints que forman el esqueleto (vacío si el modelo no es de pose).
+    pub fn skeleton(&self) -> &[[usize; 2]] {
+        &self.skeleton
+    }
 
-        let dims: Vec<usize> = shape_out.into_iter().map(|&x| x as usize).collect();
-        let array_view = ArrayViewD::from_shape(IxDyn(&dims), data_out)?;
-        let view = array_view.index_axis(Axis(0), 0);
+    pub fn infer(&mut self, rgb: &RgbImage, params: &YoloParams) -> Result<InferenceOutput> {
+        let mut output = self.infer_tiles(rgb, params)?;
+        // Los fallos de los modelos secundarios se registran y no tocan las detecciones principales.
+        for stage in &mut self.secondary {
+            stage.apply(rgb, &mut output.detections);
+        }
+        Ok(output)
+    }
 
-        let num_candidates = view.shape()[1];
-        let sx = rgb.width() as f32 / imgsz as f32;
-        let sy = rgb.height() as f32 / imgsz as f32;
+    /// Modelo principal sobre el frame completo o, si está activada, por recortes.
+    fn infer_tiles(&mut self, rgb: &RgbImage, params: &YoloParams) -> Result<InferenceOutput> {
+        let frame = (rgb.width(), rgb.height());
+        let tiles = tile_grid(frame, (params.tiling.tile_width, params.tiling.tile_height), params.tiling.overlap);
+        // Los clasificadores miran el frame completo; un frame que cabe en un recorte no se trocea.
+        if !params.tiling.enabled || self.task == ModelTask::Classify || tiles.len() < 2 {
+            return self.infer_frame(rgb, params);
+        }
 
         let mut detections = Vec::new();
+        let full_frame_ms = if params.tiling.full_frame {
+            let t_start = Instant::now();
+            detections.extend(self.infer_frame(rgb, params)?.detections);
+            Some(t_start.elapsed().as_secs_f32() * 1000.0)
+        } else {
+            None
+        };
+
+        let t_tiles = Instant::now();
+        let mut tiled = Vec::new();
+        for &tile in &tiles {
+            let crop = image::imageops::crop_imm(rgb, tile.x, tile.y, tile.width, tile.height).to_image();
+            tiled.extend(self.infer_frame(&crop, params)?.detections.into_iter().map(|det| (tile, det)));
+        }
+        let tiles_ms = t_tiles.elapsed().as_secs_f32() * 1000.0;
+
+        let t_merge = Instant::now();
+        for (tile, mut det) in tiled {
+            to_frame(&mut det, tile, frame);
+            detections.push(det);
+        }
+        // Los objetos de las zonas de solape aparecen en varios recortes (y en la pasada completa).
+        let mut detections = non_max_suppression(detections, params.iou_threshold, params.agnostic_nms);
+        detections.truncate(params.max_detections);
+
+        Ok(InferenceOutput {
+            detections,
+            tiling: Some(TileStats {
+                tiles: tiles.len() as u32,
+                tiles_ms,
+                full_frame_ms,
+                merge_ms: t_merge.elapsed().as_secs_f32() * 1000.0,
+            }),
+            ..Default::default()
+        })
+    }
+
+    fn infer_frame(&mut self, rgb: &RgbImage, params: &YoloParams) -> Result<InferenceOutput> {
+        // Entrada fija: la del modelo. Ejes dinámicos: rectángulo ajustado al aspecto del frame.
+        let input_size = self.fixed_input.unwrap_or_else(|| {
+            dynamic_input_size(
+                (rgb.width(), rgb.height()),
+                (params.input_width, params.input_height),
+                params.resize_mode,
+                self.stride,
+            )
+        });
+        let (resized, transform) = resize_to_input(rgb, input_size, params.resize_mode, &self.preprocess);
+
+        let input_tensor = image_to_tensor(&resized, self.input_element, &self.preprocess)?;
+
+        let outputs = self.session.run(ort::inputs![input_tensor])?;
+        let (dims, data_out) = extract_f32(&outputs[0], &self.preprocess)?;
+        let rules = ClassRules::new(params, &self.labels);
+        let remap = ClassRemap::new(&params.class_map, &self.labels);
+
+        // Clasificación: salida [1, C] con la puntuación de cada clase para el frame completo.
+        if self.task == ModelTask::Classify {
+            let classifications = match &remap {
+                // Se ordenan todas las clases para que la fusión no deje el top-k corto.
+                Some(remap) => remap.apply_ranking(top_k(&data_out, usize::MAX, &self.labels, |id| rules.allows(id)), params.top_k),
+                None => top_k(&data_out, params.top_k, &self.labels, |id| rules.allows(id)),
+            };
+            return Ok(InferenceOutput { classifications, ..Default::default() });
+        }
+
+        let array_view = ArrayViewD::from_shape(IxDyn(&dims), &data_out)?;
+        let view = array_view.index_axis(Axis(0), 0).into_dimensionality::<Ix2>()?;
+
+        // Los modelos de segmentación añaden una segunda salida con los prototipos [1, nm, mh, mw].
+        let proto_output = if self.task == ModelTask::Segment && outputs.len() > 1 {
+            Some(extract_f32(&outputs[1], &self.preprocess)?)
+        } else {
+            None
+        };
+        let protos = proto_output
+            .as_ref()
+            .filter(|(dims_p, _)| dims_p.len() == 4)
+            .map(|(dims_p, data_p)| ArrayView4::from_shape((dims_p[0], dims_p[1], dims_p[2], dims_p[3]), &data_p[..]))
+            .transpose()?
+            .map(|p| p.index_axis_move(Axis(0), 0));
 
-        for i in 0..num_candidates {
-            let scores = view.slice(s![4.., i]);
-            let (class_id, &max_score) = scores
-                .indexed_iter()
-                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
-                .unwrap();
-
-            if max_score > params.conf_threshold {
-                let cx = view[[0, i]];
-                let cy = view[[1, i]];
-                let w = view[[2, i]];
-                let h = view[[3, i]];
-
-                detections.push(Detection {
-                    x1: (cx - w / 2.0) * sx,
-                    y1: (cy - h / 2.0) * sy,
-                    x2: (cx + w / 2.0) * sx,
-                    y2: (cy + h / 2.0) * sy,
-                    score: max_score,
-                    class_id,
-                    label: classes.get(class_id).unwrap_or(&"objeto").to_string(),
-                });
-            }
+        let ctx = DecodeContext {
+            conf_threshold: rules.min_threshold(),
+            transform: &transform,
+            labels: &self.labels,
+            num_extra: match (&protos, self.keypoints) {
+                (Some(p), _) => p.shape()[0],
+                (None, Some(shape)) => shape.values_per_instance(),
+                (None, None) if self.task == ModelTask::Obb => 1,
+                (None, None) => 0,
+            },
+        };
+        let decoder = select_decoder(params.output_format, view.shape(), ctx.num_extra, self.end2end);
+        let mut candidates = decoder.decode(view, &ctx);
+        candidates.retain(|c| rules.accepts(&c.detection));
+        if self.task == ModelTask::Obb {
+            candidates.iter_mut().for_each(|c| apply_rotation(c, &transform));
         }
+        let candidates = finalize_candidates(candidates, decoder, params, remap.as_ref());
 
-        detections.sort_unstable_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
-        Ok(detections.into_iter().take(params.max_detections).collect())
+        let detections = candidates
+            .into_iter()
+            .map(|c| {
+                let mut det = c.detection;
+                if let Some(protos) = &protos {
+                    det.mask = Some(decode_mask(&c.extra, protos.view(), &det, &transform, input_size));
+                } else if let Some(shape) = self.keypoints {
+                    det.keypoints = decode_keypoints(&c.extra, shape, &transform);
+                }
+                det
+            })
+            .collect();
+        Ok(InferenceOutput { detections, ..Default::default() })
     }
 }
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::domain::rules::Rule;
+
+/// Reglas de todas las cámaras en un fichero YAML (`RULES_PATH`, por defecto `./rules.yaml`),
+/// indexadas por la ruta del dispositivo. Se leen al arrancar y se reescriben en cada cambio.
+pub struct RuleStore {
+    path: PathBuf,
+}
+
+impl RuleStore {
+    pub fn from_env() -> Self {
+        let path = std::env::var("RULES_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("rules.yaml"));
+        Self { path }
+    }
+
+    /// Un fichero que no existe equivale a no tener reglas.
+    pub fn load(&self) -> Result<BTreeMap<String, Vec<Rule>>> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(serde_yaml::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Escribe a un temporal y lo renombra para no dejar el fichero a medias.
+    pub fn save(&self, rules: &BTreeMap<String, Vec<Rule>>) -> Result<()> {
+        let tmp = self.path.with_extension("yaml.tmp");
+        fs::write(&tmp, serde_yaml::to_string(rules)?)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+}
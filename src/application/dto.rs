@@ -2,7 +2,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::domain::{
     camera::{CameraId, CameraMode},
-    model::{InferenceConfig, ModelId, YoloParams},
+    model::{
+        default_session_cache, InferenceConfig, InferenceSchedule, ModelId, ModelTask, PreprocessSpec, RuntimeOptions,
+        SecondaryModel, TrackerOptions, YoloParams,
+    },
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +44,20 @@ pub struct ConfigurePipelineRequest {
     pub model_name: String,
     pub onnx_path: String,
     pub yolo: YoloParams,
+    #[serde(default)]
+    pub task: ModelTask,
+    #[serde(default)]
+    pub runtime: RuntimeOptions,
+    #[serde(default)]
+    pub preprocess: Option<PreprocessSpec>,
+    #[serde(default)]
+    pub secondary: Vec<SecondaryModel>,
+    #[serde(default = "default_session_cache")]
+    pub session_cache: usize,
+    #[serde(default)]
+    pub schedule: InferenceSchedule,
+    #[serde(default)]
+    pub tracker: TrackerOptions,
 }
 
 impl From<ConfigurePipelineRequest> for (CameraId, CameraMode, InferenceConfig) {
@@ -58,8 +75,16 @@ impl From<ConfigurePipelineRequest> for (CameraId, CameraMode, InferenceConfig)
             model: ModelId {
                 name: r.model_name,
                 onnx_path: r.onnx_path,
+                labels: Vec::new(),
             },
             params: r.yolo,
+            task: r.task,
+            runtime: r.runtime,
+            preprocess: r.preprocess,
+            secondary: r.secondary,
+            session_cache: r.session_cache,
+            schedule: r.schedule,
+            tracker: r.tracker,
         };
         (cam, mode, infer)
     }
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::domain::{camera::*, model::*, stream::FrameMeta, errors::DomainResult};
+use crate::domain::{analytics::*, camera::*, events::Event, model::*, rules::Rule, stream::FrameMeta, errors::DomainResult};
 use tokio::sync::broadcast;
 
 #[async_trait]
@@ -18,11 +18,43 @@ pub trait CameraControlPort: Send + Sync {
 
 #[async_trait]
 pub trait ModelCatalogPort: Send + Sync {
-    async fn validate_model(&self, model: &ModelId) -> DomainResult<()>;
+    /// Comprueba que el modelo existe y que sus salidas encajan con la tarea pedida.
+    /// Devuelve la descripción leída (sin hash) para no tener que abrir el modelo otra vez.
+    async fn validate_model(&self, model: &ModelId, task: ModelTask) -> DomainResult<ModelInfo>;
+    /// Etiquetas por `class_id` a partir de una inspección previa (metadatos, fichero auxiliar o lista por defecto).
+    fn resolve_labels(&self, model: &ModelId, info: &ModelInfo) -> Vec<String>;
+    /// Entradas, salidas, versiones y metadatos del modelo.
+    async fn inspect_model(&self, model: &ModelId) -> DomainResult<ModelInfo>;
 }
 
 #[async_trait]
 pub trait StreamPort: Send + Sync {
     async fn configure(&self, camera: CameraId, mode: CameraMode, infer: InferenceConfig) -> DomainResult<()>;
     async fn subscribe(&self) -> DomainResult<broadcast::Receiver<(FrameMeta, Vec<u8>)>>;
+    async fn current_config(&self) -> DomainResult<Option<(CameraId, CameraMode, InferenceConfig)>>;
+    /// Proveedor y preprocesado con los que corre el modelo cargado (None si no hay ninguno).
+    async fn engine_status(&self) -> DomainResult<Option<EngineStatus>>;
+    /// Carga de modelo en curso y error de la última que falló.
+    async fn model_load_state(&self) -> DomainResult<ModelLoadState>;
+}
+
+#[async_trait]
+pub trait AnalyticsPort: Send + Sync {
+    /// Sustituye las líneas de conteo de la cámara; las que no cambian conservan sus contadores.
+    async fn set_lines(&self, camera: &CameraId, lines: Vec<CountingLine>) -> DomainResult<()>;
+    async fn lines(&self, camera: &CameraId) -> DomainResult<Vec<CountingLine>>;
+    async fn line_counts(&self, camera: &CameraId) -> DomainResult<Vec<LineCounts>>;
+    /// Sustituye las zonas de la cámara; las que no cambian conservan su estado.
+    async fn set_zones(&self, camera: &CameraId, zones: Vec<Zone>) -> DomainResult<()>;
+    async fn zones(&self, camera: &CameraId) -> DomainResult<Vec<Zone>>;
+    async fn zone_stats(&self, camera: &CameraId) -> DomainResult<Vec<ZoneStats>>;
+    /// Sustituye las reglas de la cámara; las que no cambian conservan su estado de disparo.
+    async fn set_rules(&self, camera: &CameraId, rules: Vec<Rule>) -> DomainResult<()>;
+    async fn rules(&self, camera: &CameraId) -> DomainResult<Vec<Rule>>;
+    /// Receptor del canal de eventos de todas las cámaras.
+    async fn subscribe_events(&self) -> DomainResult<broadcast::Receiver<Event>>;
+    /// Recorte JPEG guardado para un evento.
+    async fn snapshot(&self, id: u64) -> DomainResult<Option<Vec<u8>>>;
+    /// Pone a cero los contadores de líneas y las visitas terminadas de las zonas.
+    async fn reset_counts(&self, camera: &CameraId) -> DomainResult<()>;
 }
\ No newline at end of file
@@ -2,11 +2,14 @@ use std::sync::Arc;
 use tokio::sync::broadcast;
 
 use crate::{
-    application::ports::{CameraCatalogPort, CameraControlPort, ModelCatalogPort, StreamPort},
+    application::ports::{AnalyticsPort, CameraCatalogPort, CameraControlPort, ModelCatalogPort, StreamPort},
     domain::{
+        analytics::{Containment, CountingLine, LineCounts, Zone, ZoneStats},
         camera::{CameraControl, CameraId, CameraInfo, CameraMode, FrameSize, PixelFormat, SetControl},
-        errors::DomainResult,
-        model::InferenceConfig,
+        errors::{DomainError, DomainResult},
+        events::Event,
+        model::{EngineStatus, InferenceConfig, ModelId, ModelInfo, ModelLoadState, ModelTask},
+        rules::{Rule, RuleCondition, MAX_RULE_SECONDS},
         stream::FrameMeta,
     },
 };
@@ -65,23 +68,171 @@ impl PipelineService {
     }
 
     /// Configura el pipeline completo.
-    /// Antes de aplicar la configuración, valida que el modelo seleccionado sea válido.
+    /// Antes de aplicar la configuración, valida que el modelo seleccionado sea válido
+    /// y resuelve sus etiquetas si no vienen dadas.
     pub async fn configure(
         &self,
         camera: CameraId,
         mode: CameraMode,
-        infer: InferenceConfig,
+        mut infer: InferenceConfig,
     ) -> DomainResult<()> {
         // Validación preventiva antes de arrancar el hardware
-        self.model_catalog.validate_model(&infer.model).await?;
+        if !infer.runtime.profiling_path_is_safe() {
+            return Err(DomainError::InvalidInput(
+                "runtime.profiling_path debe ser una ruta relativa a PROFILING_ROOT, sin '..'".into(),
+            ));
+        }
+        let info = self.model_catalog.validate_model(&infer.model, infer.task).await?;
+        for secondary in &infer.secondary {
+            self.model_catalog.validate_model(&secondary.model_id(), ModelTask::Classify).await?;
+        }
+
+        if infer.model.labels.is_empty() {
+            infer.model.labels = self.model_catalog.resolve_labels(&infer.model, &info);
+        }
 
         // Delegar la configuración al adaptador de stream (PipelineAdapter)
         self.stream.configure(camera, mode, infer).await
     }
 
+    /// Inspecciona un modelo sin aplicarlo (entradas, salidas, opset, metadatos, hash).
+    pub async fn inspect_model(&self, model: &ModelId) -> DomainResult<ModelInfo> {
+        self.model_catalog.inspect_model(model).await
+    }
+
     /// Proporciona un receptor para el canal de difusión (broadcast)
     /// donde se publican los frames procesados y los metadatos.
     pub async fn subscribe(&self) -> DomainResult<broadcast::Receiver<(FrameMeta, Vec<u8>)>> {
         self.stream.subscribe().await
     }
+
+    /// Configuración aplicada actualmente al pipeline, si la hay.
+    pub async fn current_config(&self) -> DomainResult<Option<(CameraId, CameraMode, InferenceConfig)>> {
+        self.stream.current_config().await
+    }
+
+    /// Proveedor y preprocesado efectivos del modelo cargado.
+    pub async fn engine_status(&self) -> DomainResult<Option<EngineStatus>> {
+        self.stream.engine_status().await
+    }
+
+    /// Carga de modelo en curso y último error de carga.
+    pub async fn model_load_state(&self) -> DomainResult<ModelLoadState> {
+        self.stream.model_load_state().await
+    }
+}
+
+/// Analítica sobre las detecciones seguidas: líneas de conteo, zonas y reglas por cámara.
+#[derive(Clone)]
+pub struct AnalyticsService {
+    analytics: Arc<dyn AnalyticsPort>,
+}
+
+impl AnalyticsService {
+    pub fn new(analytics: Arc<dyn AnalyticsPort>) -> Self {
+        Self { analytics }
+    }
+
+    /// Define las líneas de conteo de una cámara. Los nombres deben ser únicos y no vacíos,
+    /// y cada línea debe tener longitud.
+    pub async fn set_lines(&self, camera: CameraId, lines: Vec<CountingLine>) -> DomainResult<()> {
+        for (i, line) in lines.iter().enumerate() {
+            if line.name.trim().is_empty() {
+                return Err(DomainError::InvalidInput("línea sin nombre".into()));
+            }
+            if lines[..i].iter().any(|l| l.name == line.name) {
+                return Err(DomainError::InvalidInput(format!("línea duplicada: {}", line.name)));
+            }
+            if line.start == line.end {
+                return Err(DomainError::InvalidInput(format!("la línea {} no tiene longitud", line.name)));
+            }
+        }
+        self.analytics.set_lines(&camera, lines).await
+    }
+
+    pub async fn lines(&self, camera: CameraId) -> DomainResult<Vec<CountingLine>> {
+        self.analytics.lines(&camera).await
+    }
+
+    pub async fn line_counts(&self, camera: CameraId) -> DomainResult<Vec<LineCounts>> {
+        self.analytics.line_counts(&camera).await
+    }
+
+    /// Define las zonas de una cámara. Los nombres deben ser únicos y no vacíos, cada polígono
+    /// necesita al menos tres vértices y `min_overlap` debe estar en (0, 1].
+    pub async fn set_zones(&self, camera: CameraId, zones: Vec<Zone>) -> DomainResult<()> {
+        for (i, zone) in zones.iter().enumerate() {
+            if zone.name.trim().is_empty() {
+                return Err(DomainError::InvalidInput("zona sin nombre".into()));
+            }
+            if zones[..i].iter().any(|z| z.name == zone.name) {
+                return Err(DomainError::InvalidInput(format!("zona duplicada: {}", zone.name)));
+            }
+            if zone.polygon.len() < 3 {
+                return Err(DomainError::InvalidInput(format!("la zona {} necesita al menos 3 vértices", zone.name)));
+            }
+            if zone.containment == Containment::Overlap && !(zone.min_overlap > 0.0 && zone.min_overlap <= 1.0) {
+                return Err(DomainError::InvalidInput(format!("min_overlap fuera de (0, 1] en la zona {}", zone.name)));
+            }
+        }
+        self.analytics.set_zones(&camera, zones).await
+    }
+
+    pub async fn zones(&self, camera: CameraId) -> DomainResult<Vec<Zone>> {
+        self.analytics.zones(&camera).await
+    }
+
+    pub async fn zone_stats(&self, camera: CameraId) -> DomainResult<Vec<ZoneStats>> {
+        self.analytics.zone_stats(&camera).await
+    }
+
+    /// Define las reglas de una cámara. Los nombres deben ser únicos y no vacíos, el enfriamiento
+    /// y la permanencia entre 0 y `MAX_RULE_SECONDS`, y los umbrales de la condición válidos.
+    pub async fn set_rules(&self, camera: CameraId, rules: Vec<Rule>) -> DomainResult<()> {
+        for (i, rule) in rules.iter().enumerate() {
+            if rule.name.trim().is_empty() {
+                return Err(DomainError::InvalidInput("regla sin nombre".into()));
+            }
+            if rules[..i].iter().any(|r| r.name == rule.name) {
+                return Err(DomainError::InvalidInput(format!("regla duplicada: {}", rule.name)));
+            }
+            // `contains` también descarta NaN e infinitos.
+            if !(0.0..=MAX_RULE_SECONDS).contains(&rule.cooldown_s) {
+                return Err(DomainError::InvalidInput(format!(
+                    "cooldown_s fuera de [0, {MAX_RULE_SECONDS}] en la regla {}",
+                    rule.name
+                )));
+            }
+            match &rule.condition {
+                RuleCondition::Dwell { seconds, .. } if !(0.0..=MAX_RULE_SECONDS).contains(seconds) => {
+                    return Err(DomainError::InvalidInput(format!(
+                        "seconds fuera de [0, {MAX_RULE_SECONDS}] en la regla {}",
+                        rule.name
+                    )));
+                }
+                RuleCondition::Detected { min_score, .. } if !(0.0..=1.0).contains(min_score) => {
+                    return Err(DomainError::InvalidInput(format!("min_score fuera de [0, 1] en la regla {}", rule.name)));
+                }
+                _ => {}
+            }
+        }
+        self.analytics.set_rules(&camera, rules).await
+    }
+
+    pub async fn rules(&self, camera: CameraId) -> DomainResult<Vec<Rule>> {
+        self.analytics.rules(&camera).await
+    }
+
+    /// Receptor de los eventos de reglas y tracks de todas las cámaras.
+    pub async fn subscribe_events(&self) -> DomainResult<broadcast::Receiver<Event>> {
+        self.analytics.subscribe_events().await
+    }
+
+    pub async fn snapshot(&self, id: u64) -> DomainResult<Option<Vec<u8>>> {
+        self.analytics.snapshot(id).await
+    }
+
+    pub async fn reset_counts(&self, camera: CameraId) -> DomainResult<()> {
+        self.analytics.reset_counts(&camera).await
+    }
 }
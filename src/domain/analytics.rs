@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::detection::Detection;
+
+/// Punto de la caja que se sigue para contar y ubicar un objeto.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Anchor {
+    Center,
+    /// Centro del borde inferior (los pies, en personas).
+    #[default]
+    Bottom,
+}
+
+impl Anchor {
+    pub fn point(self, det: &Detection) -> [f32; 2] {
+        let cx = (det.x1 + det.x2) / 2.0;
+        match self {
+            Anchor::Center => [cx, (det.y1 + det.y2) / 2.0],
+            Anchor::Bottom => [cx, det.y2],
+        }
+    }
+}
+
+/// Línea virtual de conteo en coordenadas del frame. Cruzarla hacia su lado derecho,
+/// mirando de `start` a `end` en pantalla, cuenta como entrada; hacia el izquierdo, como salida.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CountingLine {
+    pub name: String,
+    pub start: [f32; 2],
+    pub end: [f32; 2],
+    /// Clases que cuentan (etiqueta o `class_id`); vacío = todas.
+    #[serde(default)]
+    pub classes: Vec<String>,
+    #[serde(default)]
+    pub anchor: Anchor,
+}
+
+/// Entradas y salidas acumuladas de una línea, por etiqueta.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LineCounts {
+    pub name: String,
+    #[serde(rename = "in")]
+    pub entries: BTreeMap<String, u64>,
+    #[serde(rename = "out")]
+    pub exits: BTreeMap<String, u64>,
+    pub total_in: u64,
+    pub total_out: u64,
+}
+
+/// Criterio para decidir si una detección está dentro de una zona.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Containment {
+    /// Centro de la caja dentro del polígono.
+    Center,
+    /// Centro del borde inferior dentro del polígono.
+    #[default]
+    Bottom,
+    /// Fracción del área de la caja dentro del polígono `>= min_overlap`.
+    Overlap,
+}
+
+/// Zona poligonal con nombre en coordenadas del frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Zone {
+    pub name: String,
+    /// Vértices en orden (al menos tres); el polígono se cierra solo.
+    pub polygon: Vec<[f32; 2]>,
+    /// Clases que cuentan (etiqueta o `class_id`); vacío = todas.
+    #[serde(default)]
+    pub classes: Vec<String>,
+    #[serde(default)]
+    pub containment: Containment,
+    #[serde(default = "default_min_overlap")]
+    pub min_overlap: f32,
+}
+
+fn default_min_overlap() -> f32 {
+    0.5
+}
+
+/// Ocupación y permanencia de una zona en el último frame inferido, por etiqueta.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ZoneStats {
+    pub name: String,
+    /// Detecciones dentro de la zona.
+    pub occupancy: BTreeMap<String, u32>,
+    pub total: u32,
+    /// Permanencia de los objetos seguidos (con `track_id`).
+    pub dwell: BTreeMap<String, DwellStats>,
+}
+
+/// Permanencia en segundos: de los objetos que siguen en la zona y de las visitas terminadas.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DwellStats {
+    pub current_avg_s: f32,
+    pub current_max_s: f32,
+    pub visits: u64,
+    pub visit_avg_s: f32,
+    pub visit_max_s: f32,
+}
+
+/// La clase de la detección está en la lista (etiqueta sin distinguir mayúsculas o `class_id`);
+/// una lista vacía admite todas.
+pub fn class_selected(classes: &[String], det: &Detection) -> bool {
+    classes.is_empty()
+        || classes.iter().any(|key| {
+            let key = key.trim();
+            key.parse::<usize>()
+                .map_or_else(|_| key.to_lowercase() == det.label.to_lowercase(), |id| id == det.class_id)
+        })
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Detection {
@@ -9,4 +10,74 @@ pub struct Detection {
     pub score: f32,
     pub class_id: usize,
     pub label: String,
+    /// Máscara de instancia (solo modelos de segmentación).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask: Option<InstanceMask>,
+    /// Keypoints de la instancia (solo modelos de pose).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keypoints: Vec<Keypoint>,
+    /// Caja orientada (solo modelos OBB); `x1..y2` es entonces su caja envolvente.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obb: Option<RotatedBox>,
+    /// Resultados de los modelos secundarios sobre el recorte, por nombre de modelo.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, Vec<Classification>>,
+    /// Identificador estable del objeto entre frames (con el tracker activado).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_id: Option<u64>,
+    /// Actualizaciones del tracker desde que apareció el track.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_age: Option<u32>,
+}
+
+impl AsRef<Detection> for Detection {
+    fn as_ref(&self) -> &Detection {
+        self
+    }
+}
+
+/// Clase asignada al frame completo por un modelo de clasificación.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Classification {
+    pub class_id: usize,
+    pub label: String,
+    pub score: f32,
+}
+
+/// Máscara binaria codificada en RLE sobre una rejilla `width x height` que cubre
+/// el frame completo. Los tramos van por filas y empiezan siempre por ceros.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceMask {
+    pub width: u32,
+    pub height: u32,
+    pub counts: Vec<u32>,
+}
+
+/// Punto clave en coordenadas del frame; `visibility` en 0..1.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Keypoint {
+    pub x: f32,
+    pub y: f32,
+    pub visibility: f32,
+}
+
+/// Caja rotada en coordenadas del frame; `angle` en radianes, sentido horario
+/// con el eje Y hacia abajo (convención Ultralytics).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RotatedBox {
+    pub cx: f32,
+    pub cy: f32,
+    pub w: f32,
+    pub h: f32,
+    pub angle: f32,
+}
+
+impl RotatedBox {
+    /// Las cuatro esquinas en orden.
+    pub fn corners(&self) -> [[f32; 2]; 4] {
+        let (sin, cos) = self.angle.sin_cos();
+        let (dx, dy) = (self.w / 2.0, self.h / 2.0);
+        [(-dx, -dy), (dx, -dy), (dx, dy), (-dx, dy)]
+            .map(|(x, y)| [self.cx + x * cos - y * sin, self.cy + x * sin + y * cos])
+    }
 }
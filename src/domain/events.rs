@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// Evento de analítica de una cámara, difundido por un canal propio junto al de frames.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    /// Ruta del dispositivo de la cámara.
+    pub camera: String,
+    /// Milisegundos desde la época Unix.
+    pub timestamp_ms: u64,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+    /// Regla `dwell`: permanencia máxima en la zona al disparar.
+    Dwell { rule: String, zone: String, label: String, seconds: f32 },
+    /// Regla `count`: número de detecciones al disparar.
+    Count { rule: String, zone: Option<String>, count: u32 },
+    /// Regla `detected`: la detección de mayor puntuación que la cumple.
+    Detected { rule: String, label: String, score: f32, track_id: Option<u64> },
+    /// Un objeto seguido entra en escena.
+    Appeared(TrackEvent),
+    /// Un objeto seguido lleva `duration_s` en escena.
+    Stayed(TrackEvent),
+    /// Un objeto seguido deja la escena (el tracker descarta su track).
+    Disappeared(TrackEvent),
+}
+
+/// Datos de un evento del ciclo de vida de un track.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackEvent {
+    pub track_id: u64,
+    pub label: String,
+    /// Segundos desde la primera detección del track hasta la última.
+    pub duration_s: f32,
+    pub best_score: f32,
+    /// Ruta del recorte JPEG de mayor puntuación (`/api/snapshots/{id}`), si se guardan.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<String>,
+}
@@ -0,0 +1,97 @@
+use super::detection::Detection;
+
+/// Intersección sobre unión de dos cajas `[x1, y1, x2, y2]` alineadas a los ejes.
+pub fn box_iou(a: [f32; 4], b: [f32; 4]) -> f32 {
+    let ix1 = a[0].max(b[0]);
+    let iy1 = a[1].max(b[1]);
+    let ix2 = a[2].min(b[2]);
+    let iy2 = a[3].min(b[3]);
+
+    let inter = (ix2 - ix1).max(0.0) * (iy2 - iy1).max(0.0);
+    let area_a = (a[2] - a[0]).max(0.0) * (a[3] - a[1]).max(0.0);
+    let area_b = (b[2] - b[0]).max(0.0) * (b[3] - b[1]).max(0.0);
+    let union = area_a + area_b - inter;
+
+    if union <= 0.0 { 0.0 } else { inter / union }
+}
+
+/// Sutherland–Hodgman: recorta `subject` contra el polígono convexo `clip`.
+pub fn clip_polygon(subject: &[[f32; 2]], clip: &[[f32; 2]; 4]) -> Vec<[f32; 2]> {
+    // Orientación del recorte para saber qué lado de cada arista es "dentro".
+    let orientation = polygon_signed_area(clip).signum();
+    let mut output = subject.to_vec();
+
+    for i in 0..clip.len() {
+        let (a, b) = (clip[i], clip[(i + 1) % clip.len()]);
+        let inside = |p: [f32; 2]| orientation * cross(a, b, p) >= 0.0;
+
+        let input = std::mem::take(&mut output);
+        for j in 0..input.len() {
+            let (p, q) = (input[j], input[(j + 1) % input.len()]);
+            match (inside(p), inside(q)) {
+                (true, true) => output.push(q),
+                (true, false) => output.push(intersect(p, q, a, b)),
+                (false, true) => {
+                    output.push(intersect(p, q, a, b));
+                    output.push(q);
+                }
+                (false, false) => {}
+            }
+        }
+        if output.is_empty() {
+            break;
+        }
+    }
+    output
+}
+
+fn cross(a: [f32; 2], b: [f32; 2], p: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+/// Punto de corte del segmento `p-q` con la recta `a-b`.
+fn intersect(p: [f32; 2], q: [f32; 2], a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    let cp = cross(a, b, p);
+    let cq = cross(a, b, q);
+    let t = if (cp - cq).abs() < f32::EPSILON { 0.0 } else { cp / (cp - cq) };
+    [p[0] + t * (q[0] - p[0]), p[1] + t * (q[1] - p[1])]
+}
+
+fn polygon_signed_area(points: &[[f32; 2]]) -> f32 {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let (p, q) = (points[i], points[(i + 1) % n]);
+            p[0] * q[1] - q[0] * p[1]
+        })
+        .sum::<f32>()
+        / 2.0
+}
+
+pub fn polygon_area(points: &[[f32; 2]]) -> f32 {
+    if points.len() < 3 { 0.0 } else { polygon_signed_area(points).abs() }
+}
+
+/// Rectángulo `(x, y, ancho, alto)` de la caja ampliada con `padding`, recortado al frame.
+/// `None` si queda por debajo de 2 px en algún eje.
+pub fn crop_rect(det: &Detection, padding: f32, frame: (u32, u32)) -> Option<(u32, u32, u32, u32)> {
+    let pad_x = (det.x2 - det.x1) * padding.max(0.0);
+    let pad_y = (det.y2 - det.y1) * padding.max(0.0);
+    let x1 = (det.x1 - pad_x).floor().clamp(0.0, frame.0 as f32) as u32;
+    let y1 = (det.y1 - pad_y).floor().clamp(0.0, frame.1 as f32) as u32;
+    let x2 = (det.x2 + pad_x).ceil().clamp(0.0, frame.0 as f32) as u32;
+    let y2 = (det.y2 + pad_y).ceil().clamp(0.0, frame.1 as f32) as u32;
+    (x2 >= x1 + 2 && y2 >= y1 + 2).then(|| (x1, y1, x2 - x1, y2 - y1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_iou_of_identical_and_disjoint_boxes() {
+        assert_eq!(box_iou([0.0, 0.0, 10.0, 10.0], [0.0, 0.0, 10.0, 10.0]), 1.0);
+        assert_eq!(box_iou([0.0, 0.0, 10.0, 10.0], [20.0, 20.0, 30.0, 30.0]), 0.0);
+        assert_eq!(box_iou([0.0, 0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 0.0]), 0.0);
+    }
+}
@@ -3,4 +3,8 @@ pub mod camera;
 pub mod model;
 pub mod detection;
 pub mod stream;
+pub mod analytics;
+pub mod geometry;
 
+pub mod rules;
+pub mod events;
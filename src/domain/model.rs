@@ -1,26 +1,323 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelId {
     pub name: String,       // logical name, e.g. "yolo11m"
     pub onnx_path: String,  // filesystem path
+    #[serde(default)]
+    pub labels: Vec<String>, // nombres por class_id; vacío = se resuelven al cargar
+}
+
+/// Cómo se lleva el frame al tamaño de entrada del modelo.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResizeMode {
+    /// Redimensiona conservando el aspecto y rellena con gris (estándar Ultralytics).
+    #[default]
+    Letterbox,
+    /// Estira el frame al cuadrado de entrada (modelos entrenados así).
+    Stretch,
+}
+
+/// Disposición del tensor de salida del modelo.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Se deduce de la forma de la salida y de los metadatos.
+    #[default]
+    Auto,
+    /// `[1, N, 5+C]` con objectness (YOLOv5/v7).
+    YoloV5,
+    /// `[1, 4+C, N]` (YOLOv8/11).
+    YoloV8,
+    /// `[1, N, 6]` con NMS incluido (YOLOv10 / end-to-end).
+    EndToEnd,
+}
+
+/// Tipo de tarea que resuelve el modelo.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelTask {
+    /// Se deduce de los metadatos (`task`) o de la forma de las salidas.
+    #[default]
+    Auto,
+    Detect,
+    Segment,
+    Pose,
+    Obb,
+    /// Clasificación del frame completo: salida `[1, C]`, sin cajas.
+    Classify,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct YoloParams {
-    pub input_size: u32,        // 640 typical
+    pub input_width: u32,       // 640 typical; con ejes dinámicos es el máximo
+    pub input_height: u32,      // ídem; se ignoran si el modelo tiene entrada fija
     pub conf_threshold: f32,    // 0..1
     pub iou_threshold: f32,     // 0..1
     pub max_detections: usize,  // e.g. 300
+    #[serde(default)]
+    pub agnostic_nms: bool,     // NMS entre clases distintas
+    #[serde(default)]
+    pub resize_mode: ResizeMode,
+    #[serde(default)]
+    pub output_format: OutputFormat,
+    #[serde(default = "default_top_k")]
+    pub top_k: usize,           // clases devueltas en modo clasificación
+    /// Umbral por clase (nombre o `class_id`) que sustituye a `conf_threshold`.
+    #[serde(default)]
+    pub class_thresholds: BTreeMap<String, f32>,
+    /// Si no está vacía, solo se conservan estas clases (nombres o `class_id`).
+    #[serde(default)]
+    pub include_classes: Vec<String>,
+    /// Clases descartadas siempre (nombres o `class_id`).
+    #[serde(default)]
+    pub exclude_classes: Vec<String>,
+    /// Reescritura de clases a una taxonomía propia (se aplica tras los filtros y el NMS).
+    #[serde(default)]
+    pub class_map: ClassMapping,
+    /// Inferencia por recortes para objetos pequeños en frames grandes.
+    #[serde(default)]
+    pub tiling: TilingOptions,
 }
 
+fn default_top_k() -> usize { 5 }
+
 impl Default for YoloParams {
     fn default() -> Self {
         Self {
-            input_size: 640,
+            input_width: 640,
+            input_height: 640,
             conf_threshold: 0.25,
             iou_threshold: 0.45,
             max_detections: 100,
+            agnostic_nms: false,
+            resize_mode: ResizeMode::Letterbox,
+            output_format: OutputFormat::Auto,
+            top_k: default_top_k(),
+            class_thresholds: BTreeMap::new(),
+            include_classes: Vec::new(),
+            exclude_classes: Vec::new(),
+            class_map: ClassMapping::default(),
+            tiling: TilingOptions::default(),
+        }
+    }
+}
+
+/// Inferencia por recortes solapados: cada recorte pasa por el modelo a su propia escala
+/// y las cajas se devuelven al frame y se fusionan con NMS.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TilingOptions {
+    pub enabled: bool,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// Fracción de solape entre recortes vecinos (0..0.9).
+    pub overlap: f32,
+    /// Añade una pasada con el frame completo para los objetos grandes que no caben en un recorte.
+    pub full_frame: bool,
+}
+
+impl Default for TilingOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tile_width: 640,
+            tile_height: 640,
+            overlap: 0.2,
+            full_frame: true,
+        }
+    }
+}
+
+/// Tabla para publicar las clases del modelo con otra taxonomía, p.ej. coche/autobús/camión -> vehículo.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClassMapping {
+    /// Clases publicadas en orden fijo: su posición es el `class_id` de salida,
+    /// igual para cualquier modelo que use la misma tabla.
+    pub classes: Vec<String>,
+    /// Clase del modelo (nombre o `class_id`) -> clase publicada.
+    pub map: BTreeMap<String, String>,
+    /// Descarta las clases del modelo que no aparecen en `map` ni en `classes`;
+    /// si no, se publican con su nombre.
+    pub drop_unmapped: bool,
+    /// Repite el NMS con las clases ya fusionadas.
+    pub rerun_nms: bool,
+}
+
+impl ClassMapping {
+    pub fn is_identity(&self) -> bool {
+        self.classes.is_empty() && self.map.is_empty() && !self.drop_unmapped
+    }
+
+    /// Resuelve la tabla contra las etiquetas del modelo. Devuelve las etiquetas publicadas
+    /// y, por cada `class_id` del modelo, su `class_id` publicado (`None` = se descarta).
+    /// Las clases no declaradas en `classes` se añaden detrás, en orden de aparición.
+    pub fn resolve(&self, model_labels: &[String]) -> (Vec<String>, Vec<Option<usize>>) {
+        let mut labels = self.classes.clone();
+        let mut index_of = |name: &str| match labels.iter().position(|l| l.to_lowercase() == name.to_lowercase()) {
+            Some(i) => i,
+            None => {
+                labels.push(name.to_string());
+                labels.len() - 1
+            }
+        };
+
+        let targets = model_labels
+            .iter()
+            .enumerate()
+            .map(|(id, label)| {
+                let target = self.map.iter().find_map(|(key, target)| {
+                    let key = key.trim();
+                    let matches = key.parse::<usize>().map_or_else(|_| key.to_lowercase() == label.to_lowercase(), |k| k == id);
+                    matches.then_some(target)
+                });
+                let listed = self.classes.iter().any(|c| c.to_lowercase() == label.to_lowercase());
+                match target {
+                    Some(target) => Some(index_of(target)),
+                    None if self.drop_unmapped && !listed => None,
+                    None => Some(index_of(label)),
+                }
+            })
+            .collect();
+
+        (labels, targets)
+    }
+}
+
+/// Orden de los canales de color que espera el modelo.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelOrder {
+    #[default]
+    Rgb,
+    Bgr,
+}
+
+/// Disposición del tensor de entrada.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TensorLayout {
+    #[default]
+    Nchw,
+    Nhwc,
+}
+
+/// Filtro de redimensionado del frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResizeFilter {
+    /// Vecino más próximo con stretch, bilineal con letterbox.
+    #[default]
+    Auto,
+    Nearest,
+    Bilinear,
+    Bicubic,
+    Lanczos,
+}
+
+/// Cómo se convierte el frame en el tensor de entrada:
+/// `valor = (pixel * scale - mean[c]) / std[c]`, con `mean`/`std` en el orden de canales del modelo.
+/// Las entradas enteras (u8/i8) se cuantizan sin normalizar:
+/// `q = round(pixel / quant_scale) + quant_zero_point`; las salidas enteras se descuantizan con
+/// `valor = (q - output_quant_zero_point) * output_quant_scale`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreprocessSpec {
+    pub channel_order: ChannelOrder,
+    pub layout: TensorLayout,
+    pub scale: f32,
+    pub mean: [f32; 3],
+    pub std: [f32; 3],
+    pub pad_color: [u8; 3],
+    pub filter: ResizeFilter,
+    /// Escala de cuantización de las entradas enteras.
+    pub quant_scale: f32,
+    /// Zero-point de las entradas enteras; sin él, 0 en u8 y -128 en i8.
+    pub quant_zero_point: Option<i32>,
+    /// Escala de las salidas enteras, común a todas ellas. Sin ella el modelo no se carga.
+    pub output_quant_scale: Option<f32>,
+    /// Zero-point de las salidas enteras.
+    pub output_quant_zero_point: i32,
+}
+
+impl Default for PreprocessSpec {
+    fn default() -> Self {
+        // RGB/255 NCHW con relleno gris: lo que esperan las exportaciones de Ultralytics.
+        Self {
+            channel_order: ChannelOrder::Rgb,
+            layout: TensorLayout::Nchw,
+            scale: 1.0 / 255.0,
+            mean: [0.0; 3],
+            std: [1.0; 3],
+            pad_color: [114; 3],
+            filter: ResizeFilter::Auto,
+            quant_scale: 1.0,
+            quant_zero_point: None,
+            output_quant_scale: None,
+            output_quant_zero_point: 0,
+        }
+    }
+}
+
+/// Proveedor de ejecución de ONNX Runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionProviderKind {
+    Cpu,
+    Cuda,
+    TensorRt,
+    OpenVino,
+    Xnnpack,
+    CoreMl,
+    DirectMl,
+}
+
+/// Nivel de optimización del grafo que aplica ONNX Runtime al cargar el modelo.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphOptimization {
+    Disable,
+    Basic,
+    Extended,
+    #[default]
+    All,
+}
+
+/// Ajustes de la sesión de ONNX Runtime.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuntimeOptions {
+    pub intra_threads: usize,                   // 0 = lo decide ONNX Runtime
+    pub inter_threads: usize,                   // 0 = lo decide ONNX Runtime
+    pub optimization: GraphOptimization,
+    pub providers: Vec<ExecutionProviderKind>,  // por orden de preferencia; CPU siempre queda de respaldo
+    pub profiling_path: Option<String>,         // prefijo de los JSON de profiling, relativo a PROFILING_ROOT
+}
+
+impl RuntimeOptions {
+    /// El prefijo de profiling lo elige el cliente: solo se admite una ruta relativa sin `..`,
+    /// para que ORT no pueda escribir fuera de `PROFILING_ROOT`.
+    pub fn profiling_path_is_safe(&self) -> bool {
+        self.profiling_path.as_deref().is_none_or(|path| {
+            !path.trim().is_empty()
+                && std::path::Path::new(path)
+                    .components()
+                    .all(|c| matches!(c, std::path::Component::Normal(_)))
+        })
+    }
+}
+
+impl Default for RuntimeOptions {
+    fn default() -> Self {
+        Self {
+            intra_threads: 4,
+            inter_threads: 0,
+            optimization: GraphOptimization::All,
+            providers: vec![ExecutionProviderKind::Cuda, ExecutionProviderKind::Cpu],
+            profiling_path: None,
         }
     }
 }
@@ -29,4 +326,215 @@ impl Default for YoloParams {
 pub struct InferenceConfig {
     pub model: ModelId,
     pub params: YoloParams,
+    #[serde(default)]
+    pub task: ModelTask,
+    #[serde(default)]
+    pub runtime: RuntimeOptions,
+    /// Preprocesado explícito; `None` = se toma de los metadatos del modelo o el estándar de Ultralytics.
+    #[serde(default)]
+    pub preprocess: Option<PreprocessSpec>,
+    /// Modelos de clasificación que se ejecutan sobre los recortes de las detecciones.
+    #[serde(default)]
+    pub secondary: Vec<SecondaryModel>,
+    /// Motores retirados que se conservan cargados para volver a ellos sin recargar (0 = ninguno).
+    #[serde(default = "default_session_cache")]
+    pub session_cache: usize,
+    /// Cadencia de la inferencia respecto a la captura.
+    #[serde(default)]
+    pub schedule: InferenceSchedule,
+    /// Seguimiento de objetos entre frames.
+    #[serde(default)]
+    pub tracker: TrackerOptions,
+}
+
+pub fn default_session_cache() -> usize { 2 }
+
+/// Tracker estilo ByteTrack: predicción con Kalman y asociación por IoU en dos pasadas
+/// (primero las detecciones seguras, luego las dudosas contra los tracks sin emparejar).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackerOptions {
+    pub enabled: bool,
+    /// Inferencias que se conserva un track perdido antes de descartarlo.
+    pub track_buffer: u32,
+    /// Puntuación a partir de la cual una detección es segura (primera pasada).
+    pub high_threshold: f32,
+    /// Puntuación mínima para abrir un track nuevo.
+    pub new_track_threshold: f32,
+    /// IoU mínimo entre la caja predicha y la detección para asociarlas.
+    pub match_iou: f32,
+    /// Eventos del ciclo de vida de los tracks.
+    pub events: TrackEventOptions,
+}
+
+impl Default for TrackerOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            track_buffer: 30,
+            high_threshold: 0.5,
+            new_track_threshold: 0.6,
+            match_iou: 0.2,
+            events: TrackEventOptions::default(),
+        }
+    }
+}
+
+/// Eventos de aparición, permanencia y desaparición derivados de los tracks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackEventOptions {
+    pub enabled: bool,
+    /// Inferencias con detección que necesita un track para anunciar su aparición;
+    /// filtra los tracks fugaces, que tampoco anuncian su desaparición.
+    pub min_hits: u32,
+    /// Segundos en escena tras los que se anuncia la permanencia (0 = nunca).
+    pub stay_s: f32,
+    /// Guardar el recorte de mayor puntuación de cada track y enlazarlo en los eventos.
+    pub snapshots: bool,
+}
+
+impl Default for TrackEventOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_hits: 3,
+            stay_s: 10.0,
+            snapshots: true,
+        }
+    }
+}
+
+/// Qué frames capturados pasan por el modelo.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleMode {
+    /// Todos: la cámara no se vuelve a leer hasta terminar la inferencia del frame anterior,
+    /// así que se infiere cada frame leído y el stream va al ritmo de la inferencia.
+    #[default]
+    EveryFrame,
+    /// El stream va al ritmo de la cámara y el modelo procesa siempre el frame más reciente.
+    Latest,
+    /// El stream va al ritmo de la cámara y el modelo procesa uno de cada `every_n`.
+    EveryN,
+    /// El stream va al ritmo de la cámara y el modelo procesa como mucho `max_hz` frames por segundo.
+    MaxHz,
+}
+
+/// Cadencia de la inferencia. Fuera de `every_frame`, los frames sin inferencia propia se
+/// envían con las últimas detecciones disponibles.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InferenceSchedule {
+    pub mode: ScheduleMode,
+    pub every_n: u32,
+    pub max_hz: f32,
+}
+
+impl Default for InferenceSchedule {
+    fn default() -> Self {
+        Self {
+            mode: ScheduleMode::EveryFrame,
+            every_n: 2,
+            max_hz: 5.0,
+        }
+    }
+}
+
+/// Clasificador (tipo de vehículo, casco/sin casco…) aplicado al recorte de cada detección
+/// de las clases indicadas. Su top-k se añade a `Detection::attributes` bajo `name`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecondaryModel {
+    pub name: String,
+    pub onnx_path: String,
+    /// Clases primarias (etiqueta publicada o `class_id`) cuyos recortes se clasifican; vacío = todas.
+    pub classes: Vec<String>,
+    /// Lado de la entrada si el modelo no la fija.
+    pub input_size: u32,
+    /// Margen añadido al recorte, en fracción del tamaño de la caja.
+    pub padding: f32,
+    pub top_k: usize,
+    /// Puntuación mínima para publicar un atributo.
+    pub min_score: f32,
+    /// Preprocesado explícito; `None` = el de los metadatos del modelo.
+    pub preprocess: Option<PreprocessSpec>,
+}
+
+impl Default for SecondaryModel {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            onnx_path: String::new(),
+            classes: Vec::new(),
+            input_size: 224,
+            padding: 0.1,
+            top_k: 1,
+            min_score: 0.0,
+            preprocess: None,
+        }
+    }
+}
+
+impl SecondaryModel {
+    pub fn model_id(&self) -> ModelId {
+        ModelId {
+            name: self.name.clone(),
+            onnx_path: self.onnx_path.clone(),
+            labels: Vec::new(),
+        }
+    }
+}
+
+/// Estado del modelo cargado en el pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineStatus {
+    /// Modelo que está sirviendo (puede no ser aún el configurado si hay una carga en curso).
+    pub model_path: String,
+    /// Proveedor de ejecución principal con el que corre la sesión.
+    pub provider: ExecutionProviderKind,
+    /// Preprocesado efectivo (configurado o leído de los metadatos).
+    pub preprocess: PreprocessSpec,
+    /// (ancho, alto) de la entrada si el modelo la fija; `None` = ejes dinámicos.
+    pub fixed_input: Option<[u32; 2]>,
+}
+
+/// Estado de la última carga de modelo pedida al pipeline.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelLoadState {
+    /// Hay una carga en curso; mientras tanto sigue sirviendo el modelo anterior.
+    pub loading: bool,
+    /// Error de la última carga fallida; se limpia con la siguiente carga correcta.
+    pub last_error: Option<String>,
+}
+
+/// Entrada o salida del modelo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TensorInfo {
+    pub name: String,
+    pub dtype: String,    // p.ej. "f32", "f16", "u8"
+    pub shape: Vec<i64>,  // -1 = dimensión dinámica
+}
+
+/// Resultado de inspeccionar un `.onnx` sin arrancar el pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub path: String,
+    pub sha256: String,
+    pub ir_version: Option<i64>,
+    pub opset: Option<i64>,             // opset del dominio ONNX estándar
+    pub producer: Option<String>,
+    pub inputs: Vec<TensorInfo>,
+    pub outputs: Vec<TensorInfo>,
+    pub metadata: BTreeMap<String, String>,
+    pub task: ModelTask,                // tarea declarada o deducida de las salidas
+    pub imgsz: Option<[u32; 2]>,        // (alto, ancho) de entrada, si se conoce
+    pub stride: Option<u32>,
+}
+
+impl ModelInfo {
+    /// Número de dimensiones de cada salida, en orden.
+    pub fn output_ranks(&self) -> Vec<usize> {
+        self.outputs.iter().map(|o| o.shape.len()).collect()
+    }
 }
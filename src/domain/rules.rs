@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+/// Regla declarativa sobre las detecciones y zonas de una cámara. Dispara un evento cuando
+/// la condición pasa a cumplirse y no vuelve a hacerlo hasta que deja de cumplirse y ha
+/// pasado `cooldown_s` desde el último disparo.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    #[serde(flatten)]
+    pub condition: RuleCondition,
+    #[serde(default = "default_cooldown")]
+    pub cooldown_s: f32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// Límite de `cooldown_s` y de los segundos de permanencia (un día).
+pub const MAX_RULE_SECONDS: f32 = 86_400.0;
+
+fn default_cooldown() -> f32 {
+    10.0
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Condición de una regla. En las condiciones de zona las clases se comparan por etiqueta;
+/// en el resto, por etiqueta o `class_id`. Una lista de clases vacía admite todas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleCondition {
+    /// Algún objeto seguido de las clases lleva en la zona más de `seconds`.
+    Dwell {
+        zone: String,
+        #[serde(default)]
+        classes: Vec<String>,
+        seconds: f32,
+    },
+    /// El número de detecciones de las clases (en el frame o en una zona) cumple la comparación.
+    Count {
+        #[serde(default)]
+        classes: Vec<String>,
+        #[serde(default)]
+        zone: Option<String>,
+        #[serde(default)]
+        op: Comparison,
+        value: u32,
+    },
+    /// Hay alguna detección de las clases con puntuación `>= min_score`.
+    Detected {
+        #[serde(default)]
+        classes: Vec<String>,
+        #[serde(default)]
+        min_score: f32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    #[default]
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Eq,
+}
+
+impl Comparison {
+    pub fn holds(self, count: u32, value: u32) -> bool {
+        match self {
+            Comparison::Gt => count > value,
+            Comparison::Gte => count >= value,
+            Comparison::Lt => count < value,
+            Comparison::Lte => count <= value,
+            Comparison::Eq => count == value,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use super::detection::Detection;
+use super::analytics::{LineCounts, ZoneStats};
+use super::detection::{Classification, Detection};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FrameMeta {
@@ -9,6 +10,40 @@ pub struct FrameMeta {
     pub infer_ms: f32,
     pub fps_est: f32,
     pub detections: Vec<Detection>,
+    /// Pares de índices de keypoints a unir (modelos de pose).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skeleton: Vec<[usize; 2]>,
+    /// Top-k del frame completo (modelos de clasificación).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub classifications: Vec<Classification>,
+    /// Recortes procesados en el frame (inferencia por recortes).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tiling: Option<TileStats>,
+    /// Las detecciones salen de una inferencia nueva; `false` = se arrastran de un frame anterior.
+    #[serde(default)]
+    pub fresh: bool,
+    /// Tiempo entre la captura del frame inferido y la de este frame.
+    #[serde(default)]
+    pub detections_age_ms: f32,
+    /// Contadores de las líneas de la cámara (con el tracker activado).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub line_counts: Vec<LineCounts>,
+    /// Ocupación y permanencia de las zonas de la cámara.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub zones: Vec<ZoneStats>,
+}
+
+/// Resumen de la inferencia por recortes de un frame.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileStats {
+    pub tiles: u32,
+    /// Tiempo total de los recortes.
+    pub tiles_ms: f32,
+    /// Tiempo de la pasada con el frame completo, si se hizo.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_frame_ms: Option<f32>,
+    /// Tiempo de llevar las cajas al frame y fusionarlas.
+    pub merge_ms: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
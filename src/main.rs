@@ -4,7 +4,7 @@ mod adapters;
 
 use std::sync::Arc;
 use tower_http::services::ServeDir;
-use crate::application::services::{CameraService, PipelineService};
+use crate::application::services::{AnalyticsService, CameraService, PipelineService};
 use crate::adapters::{
     v4l2::{camera_repo::V4l2CameraCatalog, control_repo::V4l2CameraControl},
     onnx::{model_catalog::OnnxModelCatalog, pipeline::PipelineAdapter},
@@ -30,12 +30,14 @@ async fn main() -> anyhow::Result<()> {
 
     // 3. Instanciar Servicios (Capa de Aplicación - Casos de Uso)
     let camera_service = Arc::new(CameraService::new(camera_cat, camera_ctrl));
+    let analytics_service = Arc::new(AnalyticsService::new(pipeline_adapter.clone()));
     let pipeline_service = Arc::new(PipelineService::new(pipeline_adapter, model_cat));
 
     // 4. Configurar el Estado de la API
     let state = HttpState {
         camera: camera_service,
         pipeline: pipeline_service,
+        analytics: analytics_service,
     };
 
     // 5. Configurar el Router de Axum y Archivos Estáticos
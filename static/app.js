@@ -4,7 +4,10 @@
 
 const el = (id) => document.getElementById(id);
 let ws = null;
+let eventsWs = null;
 let lastMeta = null;
+let countingLines = [];
+let zones = [];
 let currentBrowserPath = ".";
 
 // --- UTILIDADES ---
@@ -69,6 +72,7 @@ async function browseFiles(path) {
                 } else {
                     el("modelPath").value = e.path;
                     el("fileBrowser").style.display = "none";
+                    inspectModel(e.path);
                 }
             };
             list.appendChild(item);
@@ -79,6 +83,22 @@ async function browseFiles(path) {
     }
 }
 
+// Lee entradas y metadatos del modelo elegido para rellenar imgsz y la tarea
+async function inspectModel(path) {
+    try {
+        const info = await apiGet(`/api/models/inspect?path=${encodeURIComponent(path)}`);
+        if (info.imgsz) {
+            el("imgszH").value = info.imgsz[0];
+            el("imgsz").value = info.imgsz[1];
+        }
+        el("task").value = info.task || "auto";
+        const input = info.inputs[0];
+        setStatus(`Modelo: ${info.task}, entrada ${input ? input.shape.join("x") : "?"} ${input ? input.dtype : ""}, opset ${info.opset ?? "?"}`);
+    } catch (e) {
+        setStatus(`No se pudo inspeccionar el modelo: ${e.message}`, false);
+    }
+}
+
 // --- GESTIÓN DE CÁMARA Y MODOS ---
 
 async function loadCameras() {
@@ -175,19 +195,392 @@ async function apply() {
             height: h,
             fps: parseInt(el("fpsSelect").value),
             model_path: el("modelPath").value,
-            imgsz: Number(el("imgsz").value),
+            input_width: Number(el("imgsz").value),
+            input_height: Number(el("imgszH").value),
             conf_thres: parseFloat(el("conf").value),
             iou_thres: parseFloat(el("iou").value),
-            max_det: parseInt(el("maxDet").value)
+            max_det: parseInt(el("maxDet").value),
+            agnostic_nms: el("agnosticNms").checked,
+            resize_mode: el("resizeMode").value,
+            output_format: el("outputFormat").value,
+            task: el("task").value,
+            top_k: parseInt(el("topK").value),
+            class_thresholds: parseThresholds(el("classThresholds").value),
+            include_classes: parseList(el("includeClasses").value),
+            exclude_classes: parseList(el("excludeClasses").value),
+            class_map: parseClassMap(el("classMap").value),
+            tiling: {
+                enabled: el("tiling").checked,
+                tile_width: parseInt(el("tileW").value) || 640,
+                tile_height: parseInt(el("tileH").value) || 640,
+                overlap: parseFloat(el("tileOverlap").value) || 0,
+                full_frame: el("tileFullFrame").checked
+            },
+            runtime: {
+                intra_threads: parseInt(el("intraThreads").value) || 0,
+                inter_threads: parseInt(el("interThreads").value) || 0,
+                optimization: el("graphOpt").value,
+                providers: el("providers").value.split(",").map(p => p.trim().toLowerCase()).filter(Boolean),
+                profiling_path: el("profilingPath").value.trim() || null
+            },
+            session_cache: parseInt(el("sessionCache").value) || 0,
+            tracker: {
+                enabled: el("tracker").checked,
+                track_buffer: parseInt(el("trackBuffer").value) || 30,
+                high_threshold: parseFloat(el("trackHigh").value) || 0.5,
+                new_track_threshold: parseFloat(el("trackNew").value) || 0.6,
+                match_iou: parseFloat(el("trackIou").value) || 0.2,
+                events: {
+                    enabled: el("trackEvents").checked,
+                    min_hits: parseInt(el("trackMinHits").value) || 3,
+                    stay_s: parseFloat(el("trackStay").value) || 0,
+                    snapshots: el("trackSnapshots").checked
+                }
+            },
+            schedule: {
+                mode: el("scheduleMode").value,
+                every_n: parseInt(el("everyN").value) || 1,
+                max_hz: parseFloat(el("maxHz").value) || 5
+            },
+            preprocess: el("ppManual").checked ? readPreprocess() : null,
+            secondary: parseSecondary(el("secondary").value)
         };
         
         await apiPost("/api/config", payload);
         setStatus("Configuración aplicada con éxito");
+        // El modelo se recarga en segundo plano: consultamos su estado y el proveedor efectivo
+        setTimeout(refreshProvider, 500);
     } catch (e) {
         setStatus(`Error al aplicar: ${e.message}`, false);
     }
 }
 
+// Lista "a, b" -> ["a", "b"]
+function parseList(text) {
+    return text.split(",").map(v => v.trim()).filter(Boolean);
+}
+
+// "persona:0.5, bicicleta:0.3" -> { persona: 0.5, bicicleta: 0.3 }
+function parseThresholds(text) {
+    const out = {};
+    parseList(text).forEach(entry => {
+        const idx = entry.lastIndexOf(":");
+        const value = parseFloat(entry.slice(idx + 1));
+        if (idx > 0 && !isNaN(value)) out[entry.slice(0, idx).trim()] = value;
+    });
+    return out;
+}
+
+// "coche, camión:vehículo; persona:peatón" -> tabla de clases con los destinos en orden de aparición
+function parseClassMap(text) {
+    const map = {};
+    const classes = [];
+    text.split(";").forEach(group => {
+        const idx = group.lastIndexOf(":");
+        const target = group.slice(idx + 1).trim();
+        if (idx <= 0 || !target) return;
+        parseList(group.slice(0, idx)).forEach(source => { map[source] = target; });
+        if (!classes.includes(target)) classes.push(target);
+    });
+    return {
+        classes,
+        map,
+        drop_unmapped: el("dropUnmapped").checked,
+        rerun_nms: el("rerunNms").checked
+    };
+}
+
+// Tabla de clases -> "coche, camión:vehículo; persona:peatón"
+function formatClassMap(classMap) {
+    // Se respeta el orden de `classes` para no cambiar los ids publicados al reenviar la tabla
+    const groups = Object.fromEntries((classMap.classes || []).map(c => [c, []]));
+    Object.entries(classMap.map || {}).forEach(([source, target]) => {
+        (groups[target] = groups[target] || []).push(source);
+    });
+    return Object.entries(groups)
+        .filter(([, sources]) => sources.length)
+        .map(([target, sources]) => `${sources.join(", ")}:${target}`).join("; ");
+}
+
+// Lista JSON de modelos secundarios; vacío = ninguno
+function parseSecondary(text) {
+    if (!text.trim()) return [];
+    try {
+        const models = JSON.parse(text);
+        return Array.isArray(models) ? models : [models];
+    } catch (e) {
+        throw new Error(`Modelos secundarios: JSON no válido (${e.message})`);
+    }
+}
+
+// Lista "a,b,c" -> [a, b, c] numéricos
+function parseTriplet(text, fallback) {
+    const values = text.split(",").map(v => parseFloat(v.trim()));
+    return values.length === 3 && values.every(v => !isNaN(v)) ? values : fallback;
+}
+
+function readPreprocess() {
+    return {
+        channel_order: el("ppOrder").value,
+        layout: el("ppLayout").value,
+        scale: parseFloat(el("ppScale").value) || 1 / 255,
+        mean: parseTriplet(el("ppMean").value, [0, 0, 0]),
+        std: parseTriplet(el("ppStd").value, [1, 1, 1]),
+        pad_color: parseTriplet(el("ppPad").value, [114, 114, 114]),
+        filter: el("ppFilter").value,
+        quant_scale: parseFloat(el("ppQuantScale").value) || 1,
+        quant_zero_point: el("ppQuantZero").value.trim() === "" ? null : parseInt(el("ppQuantZero").value),
+        output_quant_scale: parseFloat(el("ppOutQuantScale").value) || null,
+        output_quant_zero_point: parseInt(el("ppOutQuantZero").value) || 0
+    };
+}
+
+function fillPreprocess(pp) {
+    el("ppOrder").value = pp.channel_order || "rgb";
+    el("ppLayout").value = pp.layout || "nchw";
+    el("ppScale").value = pp.scale ?? 1 / 255;
+    el("ppMean").value = (pp.mean || [0, 0, 0]).join(",");
+    el("ppStd").value = (pp.std || [1, 1, 1]).join(",");
+    el("ppPad").value = (pp.pad_color || [114, 114, 114]).join(",");
+    el("ppFilter").value = pp.filter || "auto";
+    el("ppQuantScale").value = pp.quant_scale ?? 1;
+    el("ppQuantZero").value = pp.quant_zero_point ?? "";
+    el("ppOutQuantScale").value = pp.output_quant_scale ?? "";
+    el("ppOutQuantZero").value = pp.output_quant_zero_point ?? 0;
+}
+
+// Muestra el proveedor de ejecución con el que corre el modelo
+// El modelo se carga en segundo plano: sondeamos hasta que termine y mostramos el resultado
+async function refreshProvider() {
+    const config = await apiGet("/api/config").catch(() => null);
+    el("metricProvider").textContent = `EP: ${(config && config.active_provider) || "—"}`;
+    if (!config) return;
+    if (config.model_loading) {
+        setStatus("Cargando modelo (el anterior sigue activo)…");
+        setTimeout(refreshProvider, 1000);
+    } else if (config.model_error) {
+        setStatus(`Error cargando modelo, se mantiene ${config.active_model || "ninguno"}: ${config.model_error}`, false);
+    } else {
+        setStatus("Configuración aplicada con éxito");
+    }
+}
+
+// Pinta una máscara RLE (por filas, empezando por ceros) escalada al tamaño del frame
+function drawMask(ctx, mask, frameW, frameH) {
+    const cw = frameW / mask.width;
+    const ch = frameH / mask.height;
+    ctx.save();
+    ctx.fillStyle = "rgba(0, 255, 0, 0.35)";
+    let pos = 0;
+    mask.counts.forEach((run, i) => {
+        if (i % 2 === 1) {
+            let start = pos;
+            const end = pos + run;
+            while (start < end) {
+                const row = Math.floor(start / mask.width);
+                const col = start % mask.width;
+                const len = Math.min(end - start, mask.width - col);
+                ctx.fillRect(col * cw, row * ch, len * cw, ch);
+                start += len;
+            }
+        }
+        pos += run;
+    });
+    ctx.restore();
+}
+
+// Pinta keypoints visibles y las uniones del esqueleto
+function drawKeypoints(ctx, kps, skeleton) {
+    const visible = (k) => k && k.visibility >= 0.5;
+    ctx.save();
+    ctx.strokeStyle = "#ffcc00";
+    ctx.fillStyle = "#ff3366";
+    ctx.lineWidth = 2;
+    skeleton.forEach(([a, b]) => {
+        if (!visible(kps[a]) || !visible(kps[b])) return;
+        ctx.beginPath();
+        ctx.moveTo(kps[a].x, kps[a].y);
+        ctx.lineTo(kps[b].x, kps[b].y);
+        ctx.stroke();
+    });
+    kps.filter(visible).forEach(k => {
+        ctx.beginPath();
+        ctx.arc(k.x, k.y, 3, 0, 2 * Math.PI);
+        ctx.fill();
+    });
+    ctx.restore();
+}
+
+// Pinta una caja orientada (ángulo en radianes) usando el estilo de trazo actual
+function drawRotatedBox(ctx, obb) {
+    ctx.save();
+    ctx.translate(obb.cx, obb.cy);
+    ctx.rotate(obb.angle);
+    ctx.strokeRect(-obb.w / 2, -obb.h / 2, obb.w, obb.h);
+    ctx.restore();
+}
+
+// Pinta el top-k de un clasificador en la esquina superior izquierda
+function drawClassifications(ctx, classes) {
+    ctx.save();
+    ctx.fillStyle = "rgba(0, 0, 0, 0.6)";
+    ctx.fillRect(8, 8, 260, classes.length * 22 + 10);
+    ctx.fillStyle = "#00ff00";
+    classes.forEach((c, i) => {
+        ctx.fillText(`${c.label} ${(c.score * 100).toFixed(1)}%`, 16, 30 + i * 22);
+    });
+    ctx.restore();
+}
+
+// Pinta las líneas de conteo con sus totales de entrada/salida
+function drawLines(ctx, lines, counts) {
+    ctx.save();
+    ctx.strokeStyle = "#ffcc00";
+    ctx.fillStyle = "#ffcc00";
+    ctx.setLineDash([10, 6]);
+    lines.forEach(line => {
+        ctx.beginPath();
+        ctx.moveTo(line.start[0], line.start[1]);
+        ctx.lineTo(line.end[0], line.end[1]);
+        ctx.stroke();
+        const c = counts.find(c => c.name === line.name);
+        const text = `${line.name} ↓${c ? c.total_in : 0} ↑${c ? c.total_out : 0}`;
+        ctx.fillText(text, line.end[0] + 6, line.end[1] + 6);
+    });
+    ctx.restore();
+}
+
+// Pinta las zonas con su ocupación y la permanencia máxima de quien sigue dentro
+function drawZones(ctx, zones, stats) {
+    ctx.save();
+    ctx.strokeStyle = "#00c8ff";
+    ctx.fillStyle = "rgba(0, 200, 255, 0.12)";
+    zones.forEach(zone => {
+        if (zone.polygon.length < 3) return;
+        ctx.beginPath();
+        zone.polygon.forEach(([x, y], i) => (i ? ctx.lineTo(x, y) : ctx.moveTo(x, y)));
+        ctx.closePath();
+        ctx.fill();
+        ctx.stroke();
+        const s = stats.find(s => s.name === zone.name);
+        const maxDwell = s ? Math.max(0, ...Object.values(s.dwell || {}).map(d => d.current_max_s)) : 0;
+        ctx.fillStyle = "#00c8ff";
+        ctx.fillText(`${zone.name}: ${s ? s.total : 0} · ${maxDwell.toFixed(0)}s`, zone.polygon[0][0] + 6, zone.polygon[0][1] + 18);
+        ctx.fillStyle = "rgba(0, 200, 255, 0.12)";
+    });
+    ctx.restore();
+}
+
+// Carga las líneas de conteo de la cámara en el editor
+async function loadLines(camIdx) {
+    countingLines = await apiGet(`/api/cameras/${camIdx}/lines`).catch(() => []);
+    el("lines").value = countingLines.length ? JSON.stringify(countingLines, null, 1) : "";
+}
+
+// Carga las zonas de la cámara en el editor
+async function loadZones(camIdx) {
+    zones = await apiGet(`/api/cameras/${camIdx}/zones`).catch(() => []);
+    el("zones").value = zones.length ? JSON.stringify(zones, null, 1) : "";
+}
+
+async function saveZones() {
+    const idx = el("cameraSelect").value;
+    if (idx === "") return;
+    try {
+        const text = el("zones").value.trim();
+        const list = text ? JSON.parse(text) : [];
+        await apiPost(`/api/cameras/${idx}/zones`, { zones: Array.isArray(list) ? list : [list] });
+        await loadZones(idx);
+        setStatus("Zonas guardadas");
+    } catch (e) {
+        setStatus(`Zonas: ${e.message}`, false);
+    }
+}
+
+// Las reglas se editan en YAML; el servidor acepta también JSON
+async function loadRules(camIdx) {
+    const r = await fetch(`/api/cameras/${camIdx}/rules?format=yaml`).catch(() => null);
+    const text = r && r.ok ? await r.text() : "";
+    el("rules").value = text.trim() === "rules: []" ? "" : text;
+}
+
+async function saveRules() {
+    const idx = el("cameraSelect").value;
+    if (idx === "") return;
+    const text = el("rules").value.trim() || "rules: []";
+    const r = await fetch(`/api/cameras/${idx}/rules`, {
+        method: "POST",
+        headers: { "Content-Type": "application/yaml" },
+        body: text,
+    });
+    const data = await r.json().catch(() => ({}));
+    if (r.ok) {
+        setStatus("Reglas guardadas");
+    } else {
+        setStatus(`Reglas: ${data.error || `Error ${r.status}`}`, false);
+    }
+}
+
+// Texto corto de un evento para la lista del panel
+function describeEvent(ev) {
+    switch (ev.type) {
+        case "dwell": return `${ev.rule}: ${ev.label} en ${ev.zone} ${ev.seconds.toFixed(0)}s`;
+        case "count": return `${ev.rule}: ${ev.count}${ev.zone ? " en " + ev.zone : ""}`;
+        case "detected": return `${ev.rule}: ${ev.label} ${(ev.score * 100).toFixed(0)}%`;
+        case "appeared": return `entra ${ev.label} #${ev.track_id}`;
+        case "stayed": return `${ev.label} #${ev.track_id} lleva ${ev.duration_s.toFixed(0)}s`;
+        case "disappeared": return `sale ${ev.label} #${ev.track_id} tras ${ev.duration_s.toFixed(0)}s`;
+        default: return `${ev.type}`;
+    }
+}
+
+// Canal de eventos aparte del stream: solo JSON, sin frames
+function connectEventsWS() {
+    if (eventsWs) eventsWs.close();
+
+    const proto = location.protocol === "https:" ? "wss:" : "ws:";
+    eventsWs = new WebSocket(`${proto}//${location.host}/ws/events`);
+    eventsWs.onclose = () => setTimeout(connectEventsWS, 3000);
+    eventsWs.onmessage = (ev) => {
+        try {
+            const event = JSON.parse(ev.data);
+            const item = document.createElement("li");
+            const time = new Date(event.timestamp_ms).toLocaleTimeString();
+            item.textContent = `${time} ${describeEvent(event)} `;
+            if (event.snapshot) {
+                const link = document.createElement("a");
+                link.href = event.snapshot;
+                link.target = "_blank";
+                link.textContent = "recorte";
+                item.appendChild(link);
+            }
+            const list = el("events");
+            list.prepend(item);
+            while (list.children.length > 20) list.lastChild.remove();
+        } catch (e) {}
+    };
+}
+
+async function saveLines() {
+    const idx = el("cameraSelect").value;
+    if (idx === "") return;
+    try {
+        const text = el("lines").value.trim();
+        const lines = text ? JSON.parse(text) : [];
+        await apiPost(`/api/cameras/${idx}/lines`, { lines: Array.isArray(lines) ? lines : [lines] });
+        await loadLines(idx);
+        setStatus("Líneas de conteo guardadas");
+    } catch (e) {
+        setStatus(`Líneas de conteo: ${e.message}`, false);
+    }
+}
+
+async function resetCounts() {
+    const idx = el("cameraSelect").value;
+    if (idx === "") return;
+    await apiPost(`/api/cameras/${idx}/counts/reset`, {}).catch(e => setStatus(e.message, false));
+}
+
 function connectWS() {
     if (ws) ws.close();
     
@@ -235,11 +628,21 @@ function connectWS() {
             ctx.font = "bold 16px monospace";
 
             (lastMeta.detections || []).forEach(d => {
+                if (d.mask) drawMask(ctx, d.mask, lastMeta.width, lastMeta.height);
+                if (d.keypoints) drawKeypoints(ctx, d.keypoints, lastMeta.skeleton || []);
+
                 const bw = d.x2 - d.x1;
                 const bh = d.y2 - d.y1;
-                ctx.strokeRect(d.x1, d.y1, bw, bh);
+                if (d.obb) {
+                    drawRotatedBox(ctx, d.obb);
+                } else {
+                    ctx.strokeRect(d.x1, d.y1, bw, bh);
+                }
                 
-                const label = `${d.label} ${(d.score * 100).toFixed(0)}%`;
+                // Atributos de los modelos secundarios: mejor clase de cada uno
+                const attrs = Object.values(d.attributes || {}).map(a => a[0] && a[0].label).filter(Boolean);
+                const track = d.track_id != null ? `#${d.track_id} ` : "";
+                const label = `${track}${d.label} ${(d.score * 100).toFixed(0)}%${attrs.length ? " · " + attrs.join(", ") : ""}`;
                 const txtW = ctx.measureText(label).width;
                 ctx.fillRect(d.x1, d.y1 - 22, txtW + 10, 22);
                 ctx.fillStyle = "#000";
@@ -247,12 +650,19 @@ function connectWS() {
                 ctx.fillStyle = "#00ff00";
             });
 
+            if (lastMeta.classifications) drawClassifications(ctx, lastMeta.classifications);
+            if (zones.length) drawZones(ctx, zones, lastMeta.zones || []);
+            if (countingLines.length) drawLines(ctx, countingLines, lastMeta.line_counts || []);
+
             // Liberar memoria del bitmap
             bitmap.close();
 
             // Actualizar métricas en el UI
             el("metricFps").textContent = `FPS: ${lastMeta.fps_est.toFixed(1)}`;
-            el("metricInfer").textContent = `Infer: ${lastMeta.infer_ms.toFixed(1)}ms`;
+            el("metricInfer").textContent = `Infer: ${lastMeta.infer_ms.toFixed(1)}ms`
+                + (lastMeta.fresh ? "" : ` (+${lastMeta.detections_age_ms.toFixed(0)}ms)`);
+            const tiling = lastMeta.tiling;
+            el("metricTiles").textContent = tiling ? `Recortes: ${tiling.tiles} (${tiling.tiles_ms.toFixed(1)}ms)` : "";
         } catch (e) {
             console.error("Error renderizando frame:", e);
         }
@@ -269,10 +679,57 @@ async function init() {
         const config = await apiGet("/api/config").catch(() => null);
         if (config) {
             el("modelPath").value = config.model_path;
-            el("imgsz").value = config.imgsz;
+            el("imgsz").value = config.input_width || config.imgsz;
+            el("imgszH").value = config.input_height || config.imgsz;
             el("maxDet").value = config.max_det;
             el("conf").value = config.conf_thres;
             el("iou").value = config.iou_thres;
+            el("agnosticNms").checked = !!config.agnostic_nms;
+            el("resizeMode").value = config.resize_mode || "letterbox";
+            el("outputFormat").value = config.output_format || "auto";
+            el("task").value = config.task || "auto";
+            el("topK").value = config.top_k || 5;
+            el("classThresholds").value = Object.entries(config.class_thresholds || {})
+                .map(([k, v]) => `${k}:${v}`).join(", ");
+            el("includeClasses").value = (config.include_classes || []).join(", ");
+            el("excludeClasses").value = (config.exclude_classes || []).join(", ");
+            const classMap = config.class_map || {};
+            el("classMap").value = formatClassMap(classMap);
+            el("dropUnmapped").checked = !!classMap.drop_unmapped;
+            el("rerunNms").checked = !!classMap.rerun_nms;
+            el("secondary").value = (config.secondary || []).length ? JSON.stringify(config.secondary, null, 2) : "";
+            const tiling = config.tiling || {};
+            el("tiling").checked = !!tiling.enabled;
+            el("tileW").value = tiling.tile_width || 640;
+            el("tileH").value = tiling.tile_height || 640;
+            el("tileOverlap").value = tiling.overlap ?? 0.2;
+            el("tileFullFrame").checked = tiling.full_frame ?? true;
+            const runtime = config.runtime || {};
+            el("intraThreads").value = runtime.intra_threads ?? 4;
+            el("interThreads").value = runtime.inter_threads ?? 0;
+            el("graphOpt").value = runtime.optimization || "all";
+            el("providers").value = (runtime.providers || ["cuda", "cpu"]).join(",");
+            el("profilingPath").value = runtime.profiling_path || "";
+            el("sessionCache").value = config.session_cache ?? 2;
+            const tracker = config.tracker || {};
+            el("tracker").checked = !!tracker.enabled;
+            el("trackBuffer").value = tracker.track_buffer || 30;
+            el("trackHigh").value = tracker.high_threshold ?? 0.5;
+            el("trackNew").value = tracker.new_track_threshold ?? 0.6;
+            el("trackIou").value = tracker.match_iou ?? 0.2;
+            const trackEvents = tracker.events || {};
+            el("trackEvents").checked = !!trackEvents.enabled;
+            el("trackMinHits").value = trackEvents.min_hits || 3;
+            el("trackStay").value = trackEvents.stay_s ?? 10;
+            el("trackSnapshots").checked = trackEvents.snapshots ?? true;
+            const schedule = config.schedule || {};
+            el("scheduleMode").value = schedule.mode || "every_frame";
+            el("everyN").value = schedule.every_n || 2;
+            el("maxHz").value = schedule.max_hz || 5;
+            el("metricProvider").textContent = `EP: ${config.active_provider || "—"}`;
+            el("ppManual").checked = !!config.preprocess;
+            el("ppFields").style.display = config.preprocess ? "" : "none";
+            fillPreprocess(config.preprocess || config.active_preprocess || {});
         }
 
         // 2. Cargar lista de cámaras
@@ -282,6 +739,9 @@ async function init() {
             const initialCam = el("cameraSelect").value;
             if (initialCam !== "") {
                 await loadModes(initialCam);
+                await loadLines(initialCam);
+                await loadZones(initialCam);
+                await loadRules(initialCam);
             }
         } else {
             setStatus("No se detectaron cámaras en /dev/video*", false);
@@ -289,12 +749,25 @@ async function init() {
 
         // 3. Configurar Event Listeners
         el("cameraSelect").onchange = (e) => {
-            if (e.target.value !== "") loadModes(e.target.value);
+            if (e.target.value !== "") {
+                loadModes(e.target.value);
+                loadLines(e.target.value);
+                loadZones(e.target.value);
+                loadRules(e.target.value);
+            }
         };
         
         el("applyCameraMode").onclick = apply;
         el("applyModel").onclick = apply;
+        el("ppManual").onchange = (e) => {
+            el("ppFields").style.display = e.target.checked ? "" : "none";
+        };
         
+        el("saveLines").onclick = saveLines;
+        el("saveZones").onclick = saveZones;
+        el("saveRules").onclick = saveRules;
+        el("resetCounts").onclick = resetCounts;
+
         el("refreshControls").onclick = () => {
             const idx = el("cameraSelect").value;
             if (idx !== "") loadControls(idx);
@@ -313,6 +786,7 @@ async function init() {
 
         // 4. Iniciar WebSocket
         connectWS();
+        connectEventsWS();
         
         console.log("Dashboard listo.");
     } catch (err) {